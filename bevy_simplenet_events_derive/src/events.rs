@@ -1,12 +1,72 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, parse_quote, DeriveInput};
+use syn::{parse_macro_input, parse_quote, DeriveInput, Lit};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Stable event id set by a `simplenet` attribute.
+enum EventId
+{
+    Name(proc_macro2::TokenStream),
+    Explicit(u16),
+}
+
+/// Parses `#[simplenet(id = "name")]`, `#[simplenet(id = 1234)]`, and `#[simplenet(stable_id)]` into a stable
+/// event id.
+fn parse_event_id(ast: &DeriveInput) -> syn::Result<Option<EventId>>
+{
+    let struct_name = ast.ident.to_string();
+    let mut event_id = None;
+
+    for attr in ast.attrs.iter().filter(|a| a.path().is_ident("simplenet")) {
+        attr.parse_nested_meta(|meta| {
+            if event_id.is_some() {
+                return Err(meta.error("simplenet event id is already set"));
+            }
+
+            if meta.path.is_ident("id") {
+                match meta.value()?.parse::<Lit>()? {
+                    Lit::Str(name) => {
+                        if name.value().is_empty() {
+                            return Err(meta.error("simplenet event id must not be empty"));
+                        }
+                        event_id = Some(EventId::Name(quote! { #name }));
+                    }
+                    Lit::Int(id) => {
+                        let id: u16 = id.base10_parse()?;
+                        if id >= 0x8000 {
+                            return Err(meta.error("simplenet event id must be less than 0x8000"));
+                        }
+                        event_id = Some(EventId::Explicit(id));
+                    }
+                    _ => return Err(meta.error("simplenet event id must be a string or an integer")),
+                }
+                return Ok(());
+            }
+
+            if meta.path.is_ident("stable_id") {
+                event_id = Some(EventId::Name(
+                    quote! { ::core::concat!(::core::module_path!(), "::", #struct_name) },
+                ));
+                return Ok(());
+            }
+
+            Err(meta.error("unsupported simplenet attribute, expected `id = ..` or `stable_id`"))
+        })?;
+    }
+
+    Ok(event_id)
+}
 
 //-------------------------------------------------------------------------------------------------------------------
 
 pub(crate) fn derive_simplenet_event_impl(input: TokenStream) -> TokenStream
 {
     let mut ast = parse_macro_input!(input as DeriveInput);
+    let event_id = match parse_event_id(&ast) {
+        Ok(event_id) => event_id,
+        Err(err) => return err.to_compile_error().into(),
+    };
     ast.generics
         .make_where_clause()
         .predicates
@@ -14,8 +74,20 @@ pub(crate) fn derive_simplenet_event_impl(input: TokenStream) -> TokenStream
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
     let struct_name = &ast.ident;
 
+    let event_id = event_id.map(|event_id| match event_id {
+        EventId::Name(name) => quote! {
+            const EVENT_NAME: ::core::option::Option<&'static str> = ::core::option::Option::Some(#name);
+        },
+        EventId::Explicit(id) => quote! {
+            const EVENT_ID: ::core::option::Option<u16> = ::core::option::Option::Some(#id);
+        },
+    });
+
     TokenStream::from(quote! {
-        impl #impl_generics ::bevy_simplenet_events::SimplenetEvent for #struct_name #ty_generics #where_clause {}
+        impl #impl_generics ::bevy_simplenet_events::SimplenetEvent for #struct_name #ty_generics #where_clause
+        {
+            #event_id
+        }
     })
 }
 
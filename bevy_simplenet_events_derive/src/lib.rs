@@ -6,7 +6,13 @@ use proc_macro::TokenStream;
 
 //-------------------------------------------------------------------------------------------------------------------
 
-#[proc_macro_derive(SimplenetEvent)]
+/// Derives `SimplenetEvent`.
+///
+/// Use `#[simplenet(id = "my.event")]` to give the event a stable id derived from the name, or
+/// `#[simplenet(stable_id)]` to derive the id from the type's module path. Name-derived ids are 15-bit hashes, so
+/// they can collide once there are a few hundred named events. Use `#[simplenet(id = 1234)]` to pin the event to
+/// an explicit id below `0x8000` instead.
+#[proc_macro_derive(SimplenetEvent, attributes(simplenet))]
 pub fn derive_simplenet_event(input: TokenStream) -> TokenStream
 {
     events::derive_simplenet_event_impl(input)
@@ -1,5 +1,8 @@
-use bevy_app::{App, First};
+use std::time::Duration;
+
+use bevy_app::{App, First, Last};
 use bevy_ecs::prelude::*;
+use bevy_ecs::schedule::ScheduleLabel;
 use bevy_simplenet::{Client, ClientEvent, ClientReport};
 
 use crate::*;
@@ -10,50 +13,191 @@ type InnerClientEvent = ClientEvent<InternalEvent, InternalEvent>;
 
 //-------------------------------------------------------------------------------------------------------------------
 
-fn drain_client<E: EventPack>(world: &mut World)
+fn handle_server_schema<E: EventPack>(
+    world: &mut World,
+    queues: &EventQueueConnectorClient<E>,
+    registry: &EventRegistry<E>,
+    data: Vec<u8>,
+)
+{
+    let mismatch = match BincodeCodec::decode::<EventSchema>(&data[..]) {
+        Ok(remote) => registry.schema().compare(&remote),
+        Err(_) => Some(registry.schema().undecodable()),
+    };
+    let Some(mismatch) = mismatch else {
+        return;
+    };
+
+    tracing::warn!(?mismatch, "server event schema does not match client event schema");
+    queues.send_schema_mismatch(world, mismatch);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Removes a request from the request tracker.
+///
+/// Reports a diagnostic if the request is not tracked, since that means the server sent a bogus request id.
+fn remove_request<E: EventPack>(
+    world: &mut World,
+    client: &mut EventClientCore<E>,
+    queues: &EventQueueConnectorClient<E>,
+    request_id: u64,
+) -> Option<(u16, u16)>
+{
+    let Some(event_ids) = client.remove_request(request_id) else {
+        if client.remove_abandoned_request(request_id) {
+            tracing::debug!(request_id, "discarding server response for timed-out or cancelled request");
+            return None;
+        }
+        tracing::warn!(request_id, "ignoring server response for unknown request id");
+        queues.send_diagnostic(world, ClientDiagnostic::UnknownRequestId { request_id });
+        return None;
+    };
+
+    Some(event_ids)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn handle_stream_chunk<E: EventPack>(
+    world: &mut World,
+    client: &mut EventClientCore<E>,
+    queues: &EventQueueConnectorClient<E>,
+    chunk: InternalEvent,
+)
+{
+    let Some((request_id, payload)) = decode_request_id_frame(&chunk.data) else {
+        tracing::warn!(chunk.id, "ignoring truncated server stream chunk");
+        return;
+    };
+
+    // note: chunks for requests from before a reconnect are expected, since requests are removed when they are
+    //       lost
+    let Some((_, chunk_event_id)) = client.get_request(request_id) else {
+        tracing::debug!(request_id, "ignoring server stream chunk for unknown request id");
+        return;
+    };
+
+    if chunk.id != chunk_event_id {
+        tracing::warn!(request_id, chunk.id, chunk_event_id, "received malformed server stream chunk");
+        queues.send_diagnostic(
+            world,
+            ClientDiagnostic::MalformedResponse {
+                request_id,
+                expected_event_id: chunk_event_id,
+                received_event_id: chunk.id,
+            },
+        );
+        return;
+    }
+
+    queues.send_stream_chunk(world, chunk_event_id, request_id, payload.to_vec());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn drain_client<E: EventPack>(world: &mut World, refresh_in_first: bool)
 {
     let mut client = world.remove_resource::<EventClientCore<E>>().unwrap();
     let queues = world
         .remove_resource::<EventQueueConnectorClient<E>>()
         .unwrap();
+    let registry = world.remove_resource::<EventRegistry<E>>().unwrap();
+
+    world.resource_mut::<ClientConnectionState<E>>().refresh();
 
     // clear existing events
-    queues.clear_all(world);
+    let swap_buffers = should_swap_buffers(world, refresh_in_first);
+    queues.clear_all(world, swap_buffers);
+
+    // resolve cancelled requests
+    // - we do this before draining so responses received this tick are discarded
+    for (request_id, (request_event_id, response_event_id)) in client.take_cancelled_requests() {
+        queues.send_response(
+            world,
+            request_event_id,
+            response_event_id,
+            request_id,
+            PendingResponseData::Cancelled,
+        );
+    }
 
     // drain events
     while let Some((counter, event)) = client.next() {
         match event {
             InnerClientEvent::Report(report) => {
                 match &report {
-                    ClientReport::Connected => (),
+                    ClientReport::Connected => client.send_schema(&registry),
                     _ => queues.handle_disconnect(world), //all other variants are disconnect types
                 }
 
+                world
+                    .resource_mut::<ClientConnectionState<E>>()
+                    .update(&report);
                 queues.send_connection(world, counter, report);
             }
             InnerClientEvent::Msg(message) => {
+                if message.id == SCHEMA_EVENT_ID {
+                    handle_server_schema(world, &queues, &registry, message.data);
+                    continue;
+                }
+
+                if registry.is_stream_chunk_id(message.id) {
+                    handle_stream_chunk(world, &mut client, &queues, message);
+                    continue;
+                }
+
+                let response_event_id = registry.get_client_response_id_from_server_request_id(message.id);
+                if let Some(response_event_id) = response_event_id {
+                    let Some((request_id, payload)) = decode_request_id_frame(&message.data) else {
+                        tracing::warn!(message.id, "ignoring truncated server request");
+                        continue;
+                    };
+
+                    let token = client.server_request_token(request_id, response_event_id);
+                    queues.send_server_request(world, message.id, token, payload.to_vec());
+                    continue;
+                }
+
                 queues.send_message(world, message.id, message.data);
             }
             InnerClientEvent::Response(response, request_id) => {
-                let (request_event_id, response_event_id) = client
-                    .remove_request(request_id)
-                    .expect("request id missing");
-                if response.id != response_event_id {
-                    panic!("received invalid request id");
-                }
+                let Some((request_event_id, response_event_id)) =
+                    remove_request(world, &mut client, &queues, request_id)
+                else {
+                    continue;
+                };
 
-                queues.send_response(
-                    world,
-                    request_event_id,
-                    response_event_id,
-                    request_id,
-                    PendingResponseData::Response(response.data),
-                );
+                let data = if response.id == response_event_id {
+                    PendingResponseData::Response(response.data)
+                } else if response.id == STREAM_ERROR_EVENT_ID && registry.is_stream_chunk_id(response_event_id) {
+                    PendingResponseData::Reject
+                } else {
+                    tracing::warn!(
+                        request_id,
+                        response.id,
+                        response_event_id,
+                        "received malformed server response"
+                    );
+                    queues.send_diagnostic(
+                        world,
+                        ClientDiagnostic::MalformedResponse {
+                            request_id,
+                            expected_event_id: response_event_id,
+                            received_event_id: response.id,
+                        },
+                    );
+                    PendingResponseData::Malformed
+                };
+
+                queues.send_response(world, request_event_id, response_event_id, request_id, data);
             }
             InnerClientEvent::Ack(request_id) => {
-                let (request_event_id, response_event_id) = client
-                    .remove_request(request_id)
-                    .expect("request id missing");
+                let Some((request_event_id, response_event_id)) =
+                    remove_request(world, &mut client, &queues, request_id)
+                else {
+                    continue;
+                };
                 queues.send_response(
                     world,
                     request_event_id,
@@ -63,9 +207,11 @@ fn drain_client<E: EventPack>(world: &mut World)
                 );
             }
             InnerClientEvent::Reject(request_id) => {
-                let (request_event_id, response_event_id) = client
-                    .remove_request(request_id)
-                    .expect("request id missing");
+                let Some((request_event_id, response_event_id)) =
+                    remove_request(world, &mut client, &queues, request_id)
+                else {
+                    continue;
+                };
                 queues.send_response(
                     world,
                     request_event_id,
@@ -75,9 +221,11 @@ fn drain_client<E: EventPack>(world: &mut World)
                 );
             }
             InnerClientEvent::SendFailed(request_id) => {
-                let (request_event_id, response_event_id) = client
-                    .remove_request(request_id)
-                    .expect("request id missing");
+                let Some((request_event_id, response_event_id)) =
+                    remove_request(world, &mut client, &queues, request_id)
+                else {
+                    continue;
+                };
                 queues.send_response(
                     world,
                     request_event_id,
@@ -87,9 +235,11 @@ fn drain_client<E: EventPack>(world: &mut World)
                 );
             }
             InnerClientEvent::ResponseLost(request_id) => {
-                let (request_event_id, response_event_id) = client
-                    .remove_request(request_id)
-                    .expect("request id missing");
+                let Some((request_event_id, response_event_id)) =
+                    remove_request(world, &mut client, &queues, request_id)
+                else {
+                    continue;
+                };
                 queues.send_response(
                     world,
                     request_event_id,
@@ -101,7 +251,28 @@ fn drain_client<E: EventPack>(world: &mut World)
         }
     }
 
+    // time out requests that weren't resolved in time
+    // - we do this after draining so responses received this tick win
+    for (request_id, (request_event_id, response_event_id)) in client.expire_requests() {
+        tracing::debug!(request_id, "client request timed out");
+        queues.send_response(
+            world,
+            request_event_id,
+            response_event_id,
+            request_id,
+            PendingResponseData::TimedOut,
+        );
+    }
+
+    // reject server requests that were dropped without a response
+    client.reject_dropped_requests();
+
     world.insert_resource(client);
+    world.insert_resource(registry);
+
+    // forward messages now that the client is accessible
+    queues.forward_all(world);
+
     world.insert_resource(queues);
 }
 
@@ -114,6 +285,9 @@ pub enum PendingResponseData
     Reject,
     SendFailed,
     ResponseLost,
+    Malformed,
+    TimedOut,
+    Cancelled,
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -121,20 +295,154 @@ pub enum PendingResponseData
 pub trait SimplenetClientEventAppExt
 {
     /// Inserts a `bevy_simplenet` client for use in the events API.
+    ///
+    /// The client is refreshed in [`RefreshSet`] in schedule `First`.
     fn insert_simplenet_client<E: EventPack>(&mut self, client: Client<EventWrapper<E>>) -> &mut Self;
+
+    /// Inserts a `bevy_simplenet` client that is refreshed in [`RefreshSet`] in the given schedule.
+    ///
+    /// Use this to align the client with a fixed-tick simulation (e.g. `FixedPreUpdate`). [`RefreshSet`] can be
+    /// ordered against your own system sets with `App::configure_sets`. Event readers only see events from the
+    /// most recent refresh, so handlers should run after [`RefreshSet`] in the same schedule.
+    fn insert_simplenet_client_in<E: EventPack>(
+        &mut self,
+        client: Client<EventWrapper<E>>,
+        schedule: impl ScheduleLabel,
+    ) -> &mut Self;
+
+    /// Sets the default timeout for client requests of type `Req`.
+    ///
+    /// Requests that aren't resolved within the timeout emit [`ServerResponse::TimedOut`] (or
+    /// [`StreamResponse::TimedOut`] for stream requests), and late responses are discarded. Use
+    /// [`EventClient::request_with_timeout`] to override the timeout for one request.
+    ///
+    /// Panics if `Req` is not a registered request type.
+    fn set_simplenet_request_timeout<E: EventPack, Req: SimplenetEvent>(&mut self, timeout: Duration)
+        -> &mut Self;
+
+    /// Triggers [`NetServerMsg<T>`](NetServerMsg) for each message of type `T` received from the server.
+    ///
+    /// Observers are triggered at the end of [`RefreshSet`] for all messages received that tick, after the
+    /// messages are available to readers. Since connection-event handlers run after [`RefreshSet`], sends from
+    /// observers will fail with [`SendError::PendingConnect`] until the connection report is read.
+    ///
+    /// Panics if `T` is not registered as a message received by the client, or if the trigger was already added.
+    fn add_simplenet_client_trigger<E: EventPack, T: SimplenetEvent + Clone>(&mut self) -> &mut Self;
+
+    /// Writes [`FromServer<T>`](FromServer) bevy events for each message of type `T` received from the server.
+    ///
+    /// Events are written at the end of [`RefreshSet`], so they can be read with `EventReader` by systems and
+    /// plugins that don't know about this crate.
+    ///
+    /// Panics if `T` is not registered as a message received by the client, or if the events were already added.
+    fn add_simplenet_client_events<E: EventPack, T: SimplenetEvent + Clone>(&mut self) -> &mut Self;
+
+    /// Sends [`ToServer<T>`](ToServer) bevy events to the server with [`EventClient::send`].
+    ///
+    /// Events are drained and sent in schedule `Last`. Events that fail to send are logged and dropped.
+    ///
+    /// Panics if `T` is not a registered message type, or if the events were already added. The events aren't
+    /// keyed by channel, so this also panics if they were added for another channel.
+    fn add_simplenet_client_send_events<E: EventPack, T: SimplenetEvent>(&mut self) -> &mut Self;
 }
 
 impl SimplenetClientEventAppExt for App
 {
     fn insert_simplenet_client<E: EventPack>(&mut self, client: Client<EventWrapper<E>>) -> &mut Self
+    {
+        self.insert_simplenet_client_in(client, First)
+    }
+
+    fn insert_simplenet_client_in<E: EventPack>(
+        &mut self,
+        client: Client<EventWrapper<E>>,
+        schedule: impl ScheduleLabel,
+    ) -> &mut Self
     {
         if self.world().contains_resource::<EventClientCore<E>>() {
             panic!("event client was already inserted");
         }
 
         self.insert_resource(EventClientCore::new(client));
+        self.init_resource::<ClientConnectionState<E>>();
+
+        let schedule = schedule.intern();
+        let refresh_in_first = schedule == First.intern();
+        self.add_systems(
+            schedule,
+            (move |world: &mut World| drain_client::<E>(world, refresh_in_first)).in_set(RefreshSet),
+        );
+
+        self
+    }
+
+    fn set_simplenet_request_timeout<E: EventPack, Req: SimplenetEvent>(&mut self, timeout: Duration)
+        -> &mut Self
+    {
+        let Some(mut registry) = self.world_mut().get_resource_mut::<EventRegistry<E>>() else {
+            panic!("request type {} is not registered", std::any::type_name::<Req>());
+        };
+        let Some(request_event_id) = registry.get_request_id::<Req>() else {
+            panic!("request type {} is not registered", std::any::type_name::<Req>());
+        };
+        registry.set_request_timeout(request_event_id, timeout);
+
+        self
+    }
+
+    fn add_simplenet_client_trigger<E: EventPack, T: SimplenetEvent + Clone>(&mut self) -> &mut Self
+    {
+        if !self.world().contains_resource::<ClientMessageQueue<E, T>>() {
+            panic!("message type {} is not registered", std::any::type_name::<T>());
+        }
+
+        self.world_mut()
+            .resource_mut::<EventQueueConnectorClient<E>>()
+            .register_message_trigger::<T>();
+
+        self
+    }
+
+    fn add_simplenet_client_events<E: EventPack, T: SimplenetEvent + Clone>(&mut self) -> &mut Self
+    {
+        if !self.world().contains_resource::<ClientMessageQueue<E, T>>() {
+            panic!("message type {} is not registered", std::any::type_name::<T>());
+        }
+
+        self.add_event::<FromServer<T>>();
+        self.world_mut()
+            .resource_mut::<EventQueueConnectorClient<E>>()
+            .register_message_mirror::<T>();
+
+        self
+    }
+
+    fn add_simplenet_client_send_events<E: EventPack, T: SimplenetEvent>(&mut self) -> &mut Self
+    {
+        let registered = self
+            .world()
+            .get_resource::<EventRegistry<E>>()
+            .is_some_and(|registry| registry.get_message_id::<T>().is_some());
+        if !registered {
+            panic!("message type {} is not registered", std::any::type_name::<T>());
+        }
+        if let Some(bridge) = self.world().get_resource::<ToServerChannel<T>>() {
+            if bridge.channel() == std::any::type_name::<E>() {
+                panic!("{} was already added", std::any::type_name::<ToServer<T>>());
+            }
+            panic!(
+                "{} was already added for channel {}",
+                std::any::type_name::<ToServer<T>>(),
+                bridge.channel()
+            );
+        }
 
-        self.add_systems(First, drain_client::<E>.in_set(RefreshSet));
+        self.insert_resource(ToServerChannel::<T>::new::<E>());
+        self.add_event::<ToServer<T>>();
+        self.add_systems(
+            Last,
+            send_to_server::<E, T>.run_if(resource_exists::<EventClientCore<E>>),
+        );
 
         self
     }
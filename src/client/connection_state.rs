@@ -0,0 +1,179 @@
+use std::marker::PhantomData;
+
+use bevy_ecs::prelude::*;
+use bevy_simplenet::ClientReport;
+
+use crate::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// The connection status of a client.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum ClientStatus
+{
+    /// The client is trying to connect for the first time.
+    #[default]
+    Connecting,
+    /// The client is connected.
+    Connected,
+    /// The client is disconnected. It will try to reconnect unless it was closed.
+    Disconnected,
+    /// The client has died and will not reconnect.
+    Dead,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Client resource that tracks the client's connection status.
+///
+/// The status is updated from `ClientReport`s when they are drained in [`RefreshSet`].
+///
+/// Reading this resource does not count as reading `ClientReport::Connected`. Messages and requests will fail to
+/// send with [`SendError::PendingConnect`] until the report is read by [`ClientConnectionReader`].
+#[derive(Resource)]
+pub struct ClientConnectionState<E: EventPack>
+{
+    status: ClientStatus,
+    just_connected: bool,
+    just_disconnected: bool,
+    num_connects: u64,
+    num_disconnects: u64,
+    phantom: PhantomData<E>,
+}
+
+impl<E: EventPack> ClientConnectionState<E>
+{
+    /// Gets the current status.
+    pub fn status(&self) -> ClientStatus
+    {
+        self.status
+    }
+
+    /// Checks if the client is connected.
+    pub fn is_connected(&self) -> bool
+    {
+        self.status == ClientStatus::Connected
+    }
+
+    /// Checks if the client connected in the most recent refresh.
+    ///
+    /// The client may have disconnected again in the same refresh. This is reset every refresh, so systems in
+    /// schedules that don't run exactly once per refresh (e.g. `FixedUpdate`) should use [`client_just_connected`]
+    /// instead.
+    pub fn just_connected(&self) -> bool
+    {
+        self.just_connected
+    }
+
+    /// Checks if the client disconnected in the most recent refresh.
+    ///
+    /// This is reset every refresh, so systems in schedules that don't run exactly once per refresh (e.g.
+    /// `FixedUpdate`) should use [`client_just_disconnected`] instead.
+    pub fn just_disconnected(&self) -> bool
+    {
+        self.just_disconnected
+    }
+
+    /// Resets per-refresh state.
+    pub(crate) fn refresh(&mut self)
+    {
+        self.just_connected = false;
+        self.just_disconnected = false;
+    }
+
+    /// Updates the status from a connection report.
+    pub(crate) fn update(&mut self, report: &ClientReport)
+    {
+        let was_connected = self.is_connected();
+        self.status = match report {
+            ClientReport::Connected => ClientStatus::Connected,
+            ClientReport::Disconnected | ClientReport::ClosedByServer(_) | ClientReport::ClosedBySelf => {
+                ClientStatus::Disconnected
+            }
+            ClientReport::IsDead(_) => ClientStatus::Dead,
+        };
+
+        if !was_connected && self.is_connected() {
+            self.just_connected = true;
+            self.num_connects += 1;
+        }
+        if was_connected && !self.is_connected() {
+            self.just_disconnected = true;
+            self.num_disconnects += 1;
+        }
+    }
+}
+
+impl<E: EventPack> Default for ClientConnectionState<E>
+{
+    fn default() -> Self
+    {
+        Self {
+            status: ClientStatus::default(),
+            just_connected: false,
+            just_disconnected: false,
+            num_connects: 0,
+            num_disconnects: 0,
+            phantom: PhantomData,
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Run condition that returns `true` if the client is connected.
+///
+/// This does not read `ClientReport::Connected`. After a connect, messages and requests sent by systems using this
+/// condition fail with [`SendError::PendingConnect`] until the report is read by [`ClientConnectionReader`].
+pub fn client_connected<E: EventPack>() -> impl FnMut(Option<Res<ClientConnectionState<E>>>) -> bool + Clone
+{
+    |state: Option<Res<ClientConnectionState<E>>>| state.is_some_and(|s| s.is_connected())
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Run condition that returns `true` if the client connected since the last time this condition ran.
+///
+/// Each system using this condition tracks connects separately, so it works in schedules that don't run exactly
+/// once per refresh (e.g. `FixedUpdate`). It returns `true` once for any number of connects.
+///
+/// This does not read `ClientReport::Connected`, so systems using this condition can't send messages or requests
+/// until the report is read by [`ClientConnectionReader`]. Read the report in the same system to send on connect:
+/**
+```rust,ignore
+fn send_hello(reader: ClientConnectionReader<MyChannel>, client: EventClient<MyChannel>)
+{
+    // unblock sending
+    reader.iter().for_each(|_| ());
+    client.send(Hello).unwrap();
+}
+
+app.add_systems(Update, send_hello.run_if(client_just_connected::<MyChannel>()));
+```
+*/
+pub fn client_just_connected<E: EventPack>() -> impl FnMut(Option<Res<ClientConnectionState<E>>>) -> bool + Clone
+{
+    let mut seen = 0;
+    move |state: Option<Res<ClientConnectionState<E>>>| {
+        state.is_some_and(|s| std::mem::replace(&mut seen, s.num_connects) != s.num_connects)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Run condition that returns `true` if the client disconnected since the last time this condition ran.
+///
+/// Each system using this condition tracks disconnects separately, so it works in schedules that don't run
+/// exactly once per refresh (e.g. `FixedUpdate`). It returns `true` once for any number of disconnects.
+///
+/// This does not read connection reports. If the client reconnected in the same refresh, sending is blocked until
+/// `ClientReport::Connected` is read by [`ClientConnectionReader`].
+pub fn client_just_disconnected<E: EventPack>() -> impl FnMut(Option<Res<ClientConnectionState<E>>>) -> bool + Clone
+{
+    let mut seen = 0;
+    move |state: Option<Res<ClientConnectionState<E>>>| {
+        state.is_some_and(|s| std::mem::replace(&mut seen, s.num_disconnects) != s.num_disconnects)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
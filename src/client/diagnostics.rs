@@ -0,0 +1,30 @@
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Problems detected by the client while processing server events.
+///
+/// These usually indicate a server bug or a hostile server. See
+/// [`ClientDiagnosticReader`](crate::ClientDiagnosticReader).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ClientDiagnostic
+{
+    /// The server resolved a request that the client is not tracking.
+    ///
+    /// The response is discarded.
+    UnknownRequestId
+    {
+        request_id: u64
+    },
+    /// The server responded to a request with the wrong response event id.
+    ///
+    /// The request resolves as [`ServerResponse::Malformed`](crate::ServerResponse::Malformed).
+    MalformedResponse
+    {
+        request_id: u64,
+        /// Event id of the response type registered for the request.
+        expected_event_id: u16,
+        /// Event id received from the server.
+        received_event_id: u16,
+    },
+}
+
+//-------------------------------------------------------------------------------------------------------------------
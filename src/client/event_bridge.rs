@@ -0,0 +1,72 @@
+use std::marker::PhantomData;
+
+use bevy_ecs::prelude::*;
+
+use crate::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Bevy event written on the client for each server message of type `T`.
+///
+/// Enable with [`SimplenetClientEventAppExt::add_simplenet_client_events`].
+#[derive(Event, Debug, Clone)]
+pub struct FromServer<T: SimplenetEvent>
+{
+    /// The message.
+    pub message: T,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Bevy event that is sent to the server with [`EventClient::send`].
+///
+/// Enable with [`SimplenetClientEventAppExt::add_simplenet_client_send_events`].
+#[derive(Event, Debug, Clone)]
+pub struct ToServer<T: SimplenetEvent>
+{
+    /// The message.
+    pub message: T,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Records the channel that [`ToServer<T>`](ToServer) events are sent through.
+///
+/// The events aren't keyed by channel, so they can only be sent through one.
+#[derive(Resource)]
+pub(crate) struct ToServerChannel<T: SimplenetEvent>
+{
+    channel: &'static str,
+    phantom: PhantomData<T>,
+}
+
+impl<T: SimplenetEvent> ToServerChannel<T>
+{
+    pub(crate) fn new<E: EventPack>() -> Self
+    {
+        Self { channel: std::any::type_name::<E>(), phantom: PhantomData }
+    }
+
+    /// Gets the name of the channel.
+    pub(crate) fn channel(&self) -> &'static str
+    {
+        self.channel
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Drains [`ToServer`] events and sends them to the server.
+pub(crate) fn send_to_server<E: EventPack, T: SimplenetEvent>(
+    mut events: ResMut<Events<ToServer<T>>>,
+    client: EventClient<E>,
+)
+{
+    for ToServer { message } in events.drain() {
+        if let Err(err) = client.send(message) {
+            tracing::warn!(?err, "failed sending ToServer event");
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use bevy_ecs::prelude::*;
 use bevy_ecs::system::SystemParam;
 use bevy_simplenet::{MessageSignal, RequestSignal};
@@ -18,18 +20,74 @@ impl<'w, E: EventPack> EventClient<'w, E>
 {
     /// Sends a message to the server.
     ///
-    /// This will fail if there is a pending `ClientReport::Connected` that hasn't been read by any systems.
-    pub fn send<T: SimplenetEvent>(&self, message: T) -> MessageSignal
+    /// Returns [`SendError::PendingConnect`] if there is a pending `ClientReport::Connected` that hasn't been read
+    /// by any systems. The message can be sent again once the connect report has been read.
+    ///
+    /// If the message is handed off to the internal client, the returned signal tracks its status.
+    pub fn send<T: SimplenetEvent>(&self, message: T) -> Result<MessageSignal, SendError>
     {
         self.client.send(&self.registry, message)
     }
 
     /// Sends a request to the server.
     ///
-    /// This will fail if there is a pending `ClientReport::Connected` that hasn't been read by any systems.
-    pub fn request<Req: SimplenetEvent>(&self, request: Req) -> Result<RequestSignal, ()>
+    /// Returns [`SendError::PendingConnect`] if there is a pending `ClientReport::Connected` that hasn't been read
+    /// by any systems. The request can be sent again once the connect report has been read.
+    pub fn request<Req: SimplenetEvent>(&self, request: Req) -> Result<RequestSignal, SendError>
+    {
+        self.client.request(&self.registry, request, None)
+    }
+
+    /// Sends a request to the server that times out if it isn't resolved within `timeout`.
+    ///
+    /// Overrides the request type's default timeout. See [`Self::request`] and
+    /// [`SimplenetClientEventAppExt::set_simplenet_request_timeout`].
+    pub fn request_with_timeout<Req: SimplenetEvent>(
+        &self,
+        request: Req,
+        timeout: Duration,
+    ) -> Result<RequestSignal, SendError>
+    {
+        self.client.request(&self.registry, request, Some(timeout))
+    }
+
+    /// Cancels a request.
+    ///
+    /// The request resolves as [`ServerResponse::Cancelled`] (or [`StreamResponse::Cancelled`] for stream
+    /// requests) in the next refresh, and any late response is discarded. A cancellation notice is sent to the
+    /// server in the same refresh, where it can be read with `ServerCancelReader`. Does nothing if the request is
+    /// unknown or already resolved.
+    pub fn cancel(&self, request_id: u64)
+    {
+        self.client.cancel(request_id)
+    }
+
+    /// Responds to a server request.
+    ///
+    /// Returns [`SendError::StaleRequest`] if the request was received before the most recent reconnect, and
+    /// [`SendError::PendingConnect`] if there is a pending `ClientReport::Connected` that hasn't been read by any
+    /// systems. The request is rejected if the response fails to send.
+    pub fn respond<Resp: SimplenetEvent>(
+        &self,
+        token: ServerRequestToken,
+        response: Resp,
+    ) -> Result<MessageSignal, SendError>
+    {
+        self.client.respond(&self.registry, token, response)
+    }
+
+    /// Acknowledges a server request.
+    ///
+    /// See [`Self::respond`].
+    pub fn ack(&self, token: ServerRequestToken) -> Result<MessageSignal, SendError>
+    {
+        self.client.ack(token)
+    }
+
+    /// Rejects a server request.
+    pub fn reject(&self, token: ServerRequestToken)
     {
-        self.client.request(&self.registry, request)
+        self.client.reject(token)
     }
 
     /// Closes the client.
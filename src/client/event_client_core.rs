@@ -1,10 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use bevy_ecs::prelude::*;
 use bevy_simplenet::*;
-use bincode::Options;
 use crossbeam::channel::{Receiver, Sender};
 
 use crate::*;
@@ -26,10 +26,28 @@ pub(crate) struct EventClientCore<E: EventPack>
     pending_connect: Arc<AtomicU64>,
 
     /// Maps client requests to request/response event ids.
+    /// [ (request id, (request event id, response event id), deadline) ]
+    request_sender: Sender<(u64, (u16, u16), Option<Instant>)>,
+    request_receiver: Receiver<(u64, (u16, u16), Option<Instant>)>,
     /// [ request id : (request event id, response event id) ]
-    request_sender: Sender<(u64, (u16, u16))>,
-    request_receiver: Receiver<(u64, (u16, u16))>,
     request_map: HashMap<u64, (u16, u16)>,
+    /// [ request id : deadline ]
+    request_deadlines: HashMap<u64, Instant>,
+    /// Requests that timed out or were cancelled and are still waiting for a late response from the internal
+    /// client.
+    abandoned_requests: HashSet<u64>,
+    /// Receives requests cancelled by the user.
+    /// [ request id ]
+    cancel_sender: Sender<u64>,
+    cancel_receiver: Receiver<u64>,
+
+    /// Connection session counter, used to detect server requests from old sessions.
+    session: u64,
+
+    /// Receives server requests whose tokens were dropped without responding.
+    /// [ (request id, response event id, session) ]
+    dropped_sender: Sender<(u64, u16, u64)>,
+    dropped_receiver: Receiver<(u64, u16, u64)>,
 }
 
 impl<E: EventPack> EventClientCore<E>
@@ -38,6 +56,8 @@ impl<E: EventPack> EventClientCore<E>
     pub(crate) fn new(client: Client<EventWrapper<E>>) -> Self
     {
         let (request_sender, request_receiver) = crossbeam::channel::unbounded();
+        let (dropped_sender, dropped_receiver) = crossbeam::channel::unbounded();
+        let (cancel_sender, cancel_receiver) = crossbeam::channel::unbounded();
         Self {
             inner: client,
             counter: 0u32,
@@ -45,6 +65,13 @@ impl<E: EventPack> EventClientCore<E>
             request_sender,
             request_receiver,
             request_map: HashMap::default(),
+            request_deadlines: HashMap::default(),
+            abandoned_requests: HashSet::default(),
+            cancel_sender,
+            cancel_receiver,
+            session: 0u64,
+            dropped_sender,
+            dropped_receiver,
         }
     }
 
@@ -82,62 +109,86 @@ impl<E: EventPack> EventClientCore<E>
         }
     }
 
+    /// Sends the client's event schema to the server.
+    ///
+    /// This bypasses the pending-connect check because it is sent when the client connects.
+    pub(crate) fn send_schema(&self, registry: &EventRegistry<E>)
+    {
+        let Ok(data) = BincodeCodec::encode(registry.schema()) else {
+            tracing::error!("failed serializing client event schema");
+            return;
+        };
+
+        self.inner.send(InternalEvent { id: SCHEMA_EVENT_ID, data });
+    }
+
     /// Sends a message to the server.
-    pub(crate) fn send<T: SimplenetEvent>(&self, registry: &EventRegistry<E>, message: T) -> MessageSignal
+    pub(crate) fn send<T: SimplenetEvent>(
+        &self,
+        registry: &EventRegistry<E>,
+        message: T,
+    ) -> Result<MessageSignal, SendError>
     {
         if self.pending_connect().is_some() {
             tracing::warn!("dropping client message because there is a pending connect event");
-            return MessageSignal::new(MessageStatus::Failed);
+            return Err(SendError::PendingConnect);
         };
 
         let Some(message_event_id) = registry.get_message_id::<T>() else {
             tracing::error!("client message type is not registered");
-            return MessageSignal::new(MessageStatus::Failed);
+            return Err(SendError::UnregisteredType);
         };
 
-        let Ok(data) = bincode::DefaultOptions::new().serialize(&message) else {
+        let data = registry.encode(&message).map_err(|err| {
             tracing::error!("failed serializing client message");
-            return MessageSignal::new(MessageStatus::Failed);
-        };
+            SendError::Encode(err)
+        })?;
 
-        self.inner
-            .send(InternalEvent { id: message_event_id, data })
+        Ok(self
+            .inner
+            .send(InternalEvent { id: message_event_id, data }))
     }
 
     /// Sends a request to the server.
+    ///
+    /// If `timeout` is `None`, then the request type's default timeout is used (if any).
     pub(crate) fn request<Req: SimplenetEvent>(
         &self,
         registry: &EventRegistry<E>,
         request: Req,
-    ) -> Result<RequestSignal, ()>
+        timeout: Option<Duration>,
+    ) -> Result<RequestSignal, SendError>
     {
         if self.pending_connect().is_some() {
             tracing::warn!("dropping client request because there is a pending connect event");
-            return Err(());
+            return Err(SendError::PendingConnect);
         };
 
         let Some(request_event_id) = registry.get_request_id::<Req>() else {
             tracing::error!("client request type is not registered");
-            return Err(());
+            return Err(SendError::UnregisteredType);
         };
         let Some(response_event_id) = registry.get_response_id_from_request::<Req>() else {
             tracing::error!("no response type registered for the given client request type");
-            return Err(());
+            return Err(SendError::UnregisteredResponse);
         };
 
-        let Ok(data) = bincode::DefaultOptions::new().serialize(&request) else {
+        let data = registry.encode(&request).map_err(|err| {
             tracing::error!("failed serializing client request");
-            return Err(());
-        };
+            SendError::Encode(err)
+        })?;
 
         let result = self
             .inner
             .request(InternalEvent { id: request_event_id, data });
 
         // use channel since we are immutable
+        let deadline = timeout
+            .or_else(|| registry.get_request_timeout(request_event_id))
+            .map(|timeout| Instant::now() + timeout);
         if self
             .request_sender
-            .send((result.id(), (request_event_id, response_event_id)))
+            .send((result.id(), (request_event_id, response_event_id), deadline))
             .is_err()
         {
             tracing::error!("request tracker channel is broken");
@@ -146,17 +197,213 @@ impl<E: EventPack> EventClientCore<E>
         Ok(result)
     }
 
-    /// Removes a request from the request tracker.
-    pub(crate) fn remove_request(&mut self, request_id: u64) -> Option<(u16, u16)>
+    /// Drains pending request-tracker entries now that we are mutable.
+    fn sync_requests(&mut self)
     {
-        // drain pending request-tracker entries now that we are mutable
-        while let Ok((request_id, event_ids)) = self.request_receiver.try_recv() {
+        while let Ok((request_id, event_ids, deadline)) = self.request_receiver.try_recv() {
             self.request_map.insert(request_id, event_ids);
+            if let Some(deadline) = deadline {
+                self.request_deadlines.insert(request_id, deadline);
+            }
         }
+    }
 
+    /// Gets a request from the request tracker without removing it.
+    pub(crate) fn get_request(&mut self, request_id: u64) -> Option<(u16, u16)>
+    {
+        self.sync_requests();
+        self.request_map.get(&request_id).copied()
+    }
+
+    /// Removes a request from the request tracker.
+    pub(crate) fn remove_request(&mut self, request_id: u64) -> Option<(u16, u16)>
+    {
+        self.sync_requests();
+        self.request_deadlines.remove(&request_id);
         self.request_map.remove(&request_id)
     }
 
+    /// Removes requests whose deadlines have elapsed from the request tracker.
+    ///
+    /// Returns the expired requests. They are remembered until the internal client resolves them, so their late
+    /// responses can be discarded.
+    pub(crate) fn expire_requests(&mut self) -> Vec<(u64, (u16, u16))>
+    {
+        self.sync_requests();
+        if self.request_deadlines.is_empty() {
+            return Vec::default();
+        }
+
+        let now = Instant::now();
+        let mut expired = Vec::default();
+        self.request_deadlines.retain(|request_id, deadline| {
+            if *deadline > now {
+                return true;
+            }
+            expired.push(*request_id);
+            false
+        });
+
+        expired
+            .into_iter()
+            .filter_map(|request_id| self.abandon_request(request_id))
+            .collect()
+    }
+
+    /// Cancels a request.
+    ///
+    /// The request is removed from the request tracker in the next call to [`Self::take_cancelled_requests`].
+    pub(crate) fn cancel(&self, request_id: u64)
+    {
+        if self.cancel_sender.send(request_id).is_err() {
+            tracing::error!("request cancel channel is broken");
+        }
+    }
+
+    /// Removes cancelled requests from the request tracker.
+    ///
+    /// Returns the cancelled requests that were still being tracked. They are remembered until the internal client
+    /// resolves them, so their late responses can be discarded.
+    ///
+    /// A cancellation notice is sent to the server for each returned request unless there is a pending connect
+    /// event, in which case the requests are from an old session. Unknown and already-resolved requests are
+    /// ignored.
+    pub(crate) fn take_cancelled_requests(&mut self) -> Vec<(u64, (u16, u16))>
+    {
+        self.sync_requests();
+
+        let mut cancelled = Vec::default();
+        while let Ok(request_id) = self.cancel_receiver.try_recv() {
+            let Some(request) = self.abandon_request(request_id) else {
+                tracing::debug!(request_id, "ignoring cancel for request that is already resolved");
+                continue;
+            };
+            cancelled.push(request);
+        }
+
+        if self.pending_connect().is_none() {
+            for (request_id, _) in cancelled.iter() {
+                self.inner
+                    .send(InternalEvent { id: CANCEL_EVENT_ID, data: encode_cancel_frame(*request_id) });
+            }
+        }
+
+        cancelled
+    }
+
+    /// Removes a request from the request tracker and remembers it until the internal client resolves it.
+    fn abandon_request(&mut self, request_id: u64) -> Option<(u64, (u16, u16))>
+    {
+        self.request_deadlines.remove(&request_id);
+        let event_ids = self.request_map.remove(&request_id)?;
+        self.abandoned_requests.insert(request_id);
+        Some((request_id, event_ids))
+    }
+
+    /// Forgets an abandoned request now that the internal client resolved it.
+    ///
+    /// Returns `false` if the request wasn't abandoned.
+    pub(crate) fn remove_abandoned_request(&mut self, request_id: u64) -> bool
+    {
+        self.abandoned_requests.remove(&request_id)
+    }
+
+    /// Makes a token for a server request received in the current session.
+    pub(crate) fn server_request_token(&self, request_id: u64, response_event_id: u16) -> ServerRequestToken
+    {
+        ServerRequestToken::new(request_id, response_event_id, self.session, self.dropped_sender.clone())
+    }
+
+    /// Checks if a server request can be responded to.
+    fn check_respondable(&self, token: &ServerRequestToken) -> Result<(), SendError>
+    {
+        if token.session() != self.session {
+            let request_id = token.request_id();
+            tracing::debug!(request_id, "dropping response to server request from an old session");
+            return Err(SendError::StaleRequest);
+        }
+        if self.pending_connect().is_some() {
+            tracing::warn!("dropping client response because there is a pending connect event");
+            return Err(SendError::PendingConnect);
+        };
+
+        Ok(())
+    }
+
+    /// Sends a serialized response to the server.
+    fn send_client_response(
+        &self,
+        token: ServerRequestToken,
+        kind: ClientResponseKind,
+        payload: &[u8],
+    ) -> MessageSignal
+    {
+        let data = encode_client_response_frame(token.request_id(), kind, payload);
+        let response_event_id = token.response_event_id();
+        token.consume();
+
+        self.inner
+            .send(InternalEvent { id: response_event_id, data })
+    }
+
+    /// Sends a response to a server request.
+    pub(crate) fn respond<Resp: SimplenetEvent>(
+        &self,
+        registry: &EventRegistry<E>,
+        token: ServerRequestToken,
+        response: Resp,
+    ) -> Result<MessageSignal, SendError>
+    {
+        self.check_respondable(&token)?;
+
+        if !registry.is_client_response_type::<Resp>(token.response_event_id()) {
+            tracing::error!("client response type is not registered for the given server request");
+            return Err(SendError::UnregisteredResponse);
+        }
+
+        let payload = registry.encode(&response).map_err(|err| {
+            tracing::error!("failed serializing client response");
+            SendError::Encode(err)
+        })?;
+
+        Ok(self.send_client_response(token, ClientResponseKind::Response, &payload))
+    }
+
+    /// Acknowledges a server request.
+    pub(crate) fn ack(&self, token: ServerRequestToken) -> Result<MessageSignal, SendError>
+    {
+        self.check_respondable(&token)?;
+
+        Ok(self.send_client_response(token, ClientResponseKind::Ack, &[]))
+    }
+
+    /// Rejects a server request.
+    ///
+    /// Requests from old sessions are ignored.
+    pub(crate) fn reject(&self, token: ServerRequestToken)
+    {
+        if token.session() != self.session {
+            token.consume();
+            return;
+        }
+
+        self.send_client_response(token, ClientResponseKind::Reject, &[]);
+    }
+
+    /// Rejects server requests whose tokens were dropped.
+    pub(crate) fn reject_dropped_requests(&self)
+    {
+        while let Ok((request_id, response_event_id, session)) = self.dropped_receiver.try_recv() {
+            if session != self.session {
+                continue;
+            }
+
+            let data = encode_client_response_frame(request_id, ClientResponseKind::Reject, &[]);
+            self.inner
+                .send(InternalEvent { id: response_event_id, data });
+        }
+    }
+
     /// Closes the client.
     pub(crate) fn close(&self)
     {
@@ -166,16 +413,12 @@ impl<E: EventPack> EventClientCore<E>
     /// Extracts the next client event.
     pub(crate) fn next(&mut self) -> Option<(u32, ClientEventFrom<EventWrapper<E>>)>
     {
-        let Some(next) = self.inner.next() else {
-            return None;
-        };
+        let next = self.inner.next()?;
         self.counter += 1;
 
-        match &next {
-            ClientEventFrom::<EventWrapper<E>>::Report(ClientReport::Connected) => {
-                self.set_pending_connect(Some(self.counter));
-            }
-            _ => (),
+        if let ClientEventFrom::<EventWrapper<E>>::Report(ClientReport::Connected) = &next {
+            self.set_pending_connect(Some(self.counter));
+            self.session += 1;
         }
 
         Some((self.counter, next))
@@ -1,3 +1,4 @@
+use std::any::TypeId;
 use std::collections::HashMap;
 use std::marker::PhantomData;
 
@@ -5,7 +6,6 @@ use bevy_cobweb::prelude::*;
 use bevy_ecs::prelude::*;
 use bevy_ecs::world::Command;
 use bevy_simplenet::ClientReport;
-use bincode::Options;
 
 use crate::*;
 
@@ -13,6 +13,7 @@ use crate::*;
 
 struct PendingResponse
 {
+    response_event_id: u16,
     request_id: u64,
     data: PendingResponseData,
 }
@@ -26,13 +27,37 @@ fn clear_connection_queue<E: EventPack>(mut queue: ResMut<ClientConnectionQueue<
 
 //-------------------------------------------------------------------------------------------------------------------
 
-fn clear_message_queue<E: EventPack, T: SimplenetEvent>(mut queue: ResMut<ClientMessageQueue<E, T>>)
+fn clear_diagnostic_queue<E: EventPack>(mut queue: ResMut<ClientDiagnosticQueue<E>>)
 {
     queue.clear();
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
+fn clear_decode_error_queue<E: EventPack>(mut queue: ResMut<ClientDecodeErrorQueue<E>>)
+{
+    queue.clear();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn clear_message_queue<E: EventPack, T: SimplenetEvent>(
+    In(swap_buffers): In<bool>,
+    mut queue: ResMut<ClientMessageQueue<E, T>>,
+)
+{
+    queue.clear(swap_buffers);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn clear_message_session<E: EventPack, T: SimplenetEvent>(mut queue: ResMut<ClientMessageQueue<E, T>>)
+{
+    queue.clear_session();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 fn clear_response_queue<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>(
     mut queue: ResMut<ClientResponseQueue<E, Req, Resp>>,
 )
@@ -51,6 +76,33 @@ fn reset_response_queue<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>
 
 //-------------------------------------------------------------------------------------------------------------------
 
+fn clear_stream_queue<E: EventPack, Req: SimplenetEvent, Chunk: SimplenetEvent>(
+    mut queue: ResMut<ClientStreamQueue<E, Req, Chunk>>,
+)
+{
+    queue.clear();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn reset_stream_queue<E: EventPack, Req: SimplenetEvent, Chunk: SimplenetEvent>(
+    mut queue: ResMut<ClientStreamQueue<E, Req, Chunk>>,
+)
+{
+    queue.reset();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn clear_request_queue<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>(
+    mut queue: ResMut<ClientRequestQueue<E, Req, Resp>>,
+)
+{
+    queue.clear();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 fn send_connection<E: EventPack>(
     In((counter, report)): In<(u32, ClientReport)>,
     mut queue: ResMut<ClientConnectionQueue<E>>,
@@ -61,14 +113,36 @@ fn send_connection<E: EventPack>(
 
 //-------------------------------------------------------------------------------------------------------------------
 
-fn send_message<E: EventPack, T: SimplenetEvent>(
-    In(data): In<Vec<u8>>,
+fn send_schema_mismatch<E: EventPack>(
+    In(mismatch): In<SchemaMismatch>,
+    mut queue: ResMut<ClientConnectionQueue<E>>,
+)
+{
+    queue.send_schema_mismatch(mismatch);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn send_diagnostic<E: EventPack>(In(diagnostic): In<ClientDiagnostic>, mut queue: ResMut<ClientDiagnosticQueue<E>>)
+{
+    queue.send(diagnostic);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn send_message<E: EventPack, T: SimplenetEvent, C: EventCodec>(
+    In((message_event_id, data)): In<(u16, Vec<u8>)>,
     mut queue: ResMut<ClientMessageQueue<E, T>>,
+    mut errors: ResMut<ClientDecodeErrorQueue<E>>,
 )
 {
-    let Ok(message) = bincode::DefaultOptions::new().deserialize(&data[..]) else {
-        tracing::warn!("received server message that failed to deserialize");
-        return;
+    let message = match C::decode(&data[..]) {
+        Ok(message) => message,
+        Err(err) => {
+            tracing::warn!(message_event_id, "received server message that failed to deserialize");
+            errors.send(DecodeError::new::<T>(message_event_id, err));
+            return;
+        }
     };
 
     queue.send(message);
@@ -76,23 +150,73 @@ fn send_message<E: EventPack, T: SimplenetEvent>(
 
 //-------------------------------------------------------------------------------------------------------------------
 
-fn send_response<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>(
+fn send_response<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent, C: EventCodec>(
     In(response): In<PendingResponse>,
     mut queue: ResMut<ClientResponseQueue<E, Req, Resp>>,
+    mut errors: ResMut<ClientDecodeErrorQueue<E>>,
 )
 {
     let response = match response.data {
-        PendingResponseData::Response(data) => {
-            let Ok(resp_ser) = bincode::DefaultOptions::new().deserialize(&data[..]) else {
-                tracing::warn!("received server response that failed to deserialize");
-                return;
-            };
-            ServerResponse::<Resp>::Response(resp_ser, response.request_id)
-        }
+        PendingResponseData::Response(data) => match C::decode(&data[..]) {
+            Ok(resp_ser) => ServerResponse::<Resp>::Response(resp_ser, response.request_id),
+            Err(err) => {
+                tracing::warn!(response.request_id, "received server response that failed to deserialize");
+                errors.send(DecodeError::new::<Resp>(response.response_event_id, err));
+                ServerResponse::<Resp>::Malformed(response.request_id)
+            }
+        },
         PendingResponseData::Ack => ServerResponse::Ack(response.request_id),
         PendingResponseData::Reject => ServerResponse::Reject(response.request_id),
         PendingResponseData::SendFailed => ServerResponse::SendFailed(response.request_id),
         PendingResponseData::ResponseLost => ServerResponse::ResponseLost(response.request_id),
+        PendingResponseData::Malformed => ServerResponse::Malformed(response.request_id),
+        PendingResponseData::TimedOut => ServerResponse::TimedOut(response.request_id),
+        PendingResponseData::Cancelled => ServerResponse::Cancelled(response.request_id),
+    };
+
+    queue.send(response);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn send_stream_chunk<E: EventPack, Req: SimplenetEvent, Chunk: SimplenetEvent, C: EventCodec>(
+    In((chunk_event_id, request_id, data)): In<(u16, u64, Vec<u8>)>,
+    mut queue: ResMut<ClientStreamQueue<E, Req, Chunk>>,
+    mut errors: ResMut<ClientDecodeErrorQueue<E>>,
+)
+{
+    let chunk = match C::decode(&data[..]) {
+        Ok(chunk) => chunk,
+        Err(err) => {
+            tracing::warn!(request_id, "received server stream chunk that failed to deserialize");
+            errors.send(DecodeError::new::<Chunk>(chunk_event_id, err));
+            return;
+        }
+    };
+
+    queue.send(StreamResponse::Chunk(chunk, request_id));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn send_stream_end<E: EventPack, Req: SimplenetEvent, Chunk: SimplenetEvent>(
+    In(response): In<PendingResponse>,
+    mut queue: ResMut<ClientStreamQueue<E, Req, Chunk>>,
+)
+{
+    let request_id = response.request_id;
+    let response = match response.data {
+        PendingResponseData::Response(_) => {
+            tracing::warn!(request_id, "received server response for a stream request");
+            StreamResponse::<Chunk>::Malformed(request_id)
+        }
+        PendingResponseData::Ack => StreamResponse::Done(request_id),
+        PendingResponseData::Reject => StreamResponse::Error(request_id),
+        PendingResponseData::SendFailed => StreamResponse::SendFailed(request_id),
+        PendingResponseData::ResponseLost => StreamResponse::ResponseLost(request_id),
+        PendingResponseData::Malformed => StreamResponse::Malformed(request_id),
+        PendingResponseData::TimedOut => StreamResponse::TimedOut(request_id),
+        PendingResponseData::Cancelled => StreamResponse::Cancelled(request_id),
     };
 
     queue.send(response);
@@ -100,13 +224,68 @@ fn send_response<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>(
 
 //-------------------------------------------------------------------------------------------------------------------
 
+fn send_server_request<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent, C: EventCodec>(
+    In((request_event_id, token, data)): In<(u16, ServerRequestToken, Vec<u8>)>,
+    mut queue: ResMut<ClientRequestQueue<E, Req, Resp>>,
+    mut errors: ResMut<ClientDecodeErrorQueue<E>>,
+)
+{
+    let request = match C::decode(&data[..]) {
+        Ok(request) => request,
+        Err(err) => {
+            // note: dropping the token rejects the request
+            tracing::warn!(request_event_id, "received server request that failed to deserialize");
+            errors.send(DecodeError::new::<Req>(request_event_id, err));
+            return;
+        }
+    };
+
+    queue.send(token, request);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+type SendServerRequestCallback = CallbackWith<(), (ServerRequestToken, Vec<u8>)>;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn trigger_messages<E: EventPack, T: SimplenetEvent + Clone>(
+    mut commands: Commands,
+    queue: Res<ClientMessageQueue<E, T>>,
+)
+{
+    for message in queue.iter() {
+        commands.trigger(NetServerMsg { message: message.clone() });
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn mirror_messages<E: EventPack, T: SimplenetEvent + Clone>(
+    mut events: EventWriter<FromServer<T>>,
+    queue: Res<ClientMessageQueue<E, T>>,
+)
+{
+    events.send_batch(
+        queue
+            .iter()
+            .map(|message| FromServer { message: message.clone() }),
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Provides access to registered event queues.
 #[derive(Resource)]
 pub(crate) struct EventQueueConnectorClient<E: EventPack>
 {
     /// Cached systems for clearing event queues.
-    clear_message_queues: Vec<Callback<()>>,
+    clear_message_queues: Vec<CallbackWith<(), bool>>,
     clear_response_queues: Vec<Callback<()>>,
+    clear_request_queues: Vec<Callback<()>>,
+
+    /// Cached systems for discarding messages from old sessions.
+    clear_message_sessions: Vec<Callback<()>>,
 
     /// Cached systems for resetting stale responses.
     reset_response_queues: Vec<Callback<()>>,
@@ -117,18 +296,34 @@ pub(crate) struct EventQueueConnectorClient<E: EventPack>
     /// Cached systems for sending response events.
     /// [ response event id : [ request event id : callback ] ]
     send_responses: HashMap<u16, HashMap<u16, CallbackWith<(), PendingResponse>>>,
+    /// Cached systems for sending stream chunk events.
+    /// [ chunk event id : callback ]
+    send_stream_chunks: HashMap<u16, CallbackWith<(), (u64, Vec<u8>)>>,
+    /// Cached systems for sending server request events.
+    /// [ request event id : callback ]
+    send_server_requests: HashMap<u16, SendServerRequestCallback>,
+
+    /// Cached systems for forwarding messages to observers and bevy events.
+    /// [ (forwarded event type id, callback) ]
+    message_forwarders: Vec<(TypeId, Callback<()>)>,
 
     phantom: PhantomData<E>,
 }
 
 impl<E: EventPack> EventQueueConnectorClient<E>
 {
-    pub(crate) fn register_message<T: SimplenetEvent>(&mut self, message_event_id: u16)
+    pub(crate) fn register_message<T: SimplenetEvent, C: EventCodec>(&mut self, message_event_id: u16)
     {
         // add clear-message
         self.clear_message_queues
+            .push(CallbackWith::new(|world: &mut World, swap_buffers: bool| {
+                syscall(world, swap_buffers, clear_message_queue::<E, T>);
+            }));
+
+        // add clear-session
+        self.clear_message_sessions
             .push(Callback::new(|world: &mut World| {
-                syscall(world, (), clear_message_queue::<E, T>);
+                syscall(world, (), clear_message_session::<E, T>);
             }));
 
         // add send-message
@@ -136,8 +331,8 @@ impl<E: EventPack> EventQueueConnectorClient<E>
             .send_messages
             .insert(
                 message_event_id,
-                CallbackWith::new(|world: &mut World, data: Vec<u8>| {
-                    syscall(world, data, send_message::<E, T>);
+                CallbackWith::new(move |world: &mut World, data: Vec<u8>| {
+                    syscall(world, (message_event_id, data), send_message::<E, T, C>);
                 }),
             )
             .is_some()
@@ -146,7 +341,7 @@ impl<E: EventPack> EventQueueConnectorClient<E>
         }
     }
 
-    pub(crate) fn register_response<Req: SimplenetEvent, Resp: SimplenetEvent>(
+    pub(crate) fn register_response<Req: SimplenetEvent, Resp: SimplenetEvent, C: EventCodec>(
         &mut self,
         request_event_id: u16,
         response_event_id: u16,
@@ -172,7 +367,7 @@ impl<E: EventPack> EventQueueConnectorClient<E>
             .insert(
                 request_event_id,
                 CallbackWith::new(|world: &mut World, response: PendingResponse| {
-                    syscall(world, response, send_response::<E, Req, Resp>);
+                    syscall(world, response, send_response::<E, Req, Resp, C>);
                 }),
             )
             .is_some()
@@ -181,28 +376,153 @@ impl<E: EventPack> EventQueueConnectorClient<E>
         }
     }
 
-    pub(crate) fn clear_all(&self, world: &mut World)
+    pub(crate) fn register_stream<Req: SimplenetEvent, Chunk: SimplenetEvent, C: EventCodec>(
+        &mut self,
+        request_event_id: u16,
+        chunk_event_id: u16,
+    )
+    {
+        // add clear-stream
+        self.clear_response_queues
+            .push(Callback::new(|world: &mut World| {
+                syscall(world, (), clear_stream_queue::<E, Req, Chunk>);
+            }));
+
+        // add reset-stream
+        self.reset_response_queues
+            .push(Callback::new(|world: &mut World| {
+                syscall(world, (), reset_stream_queue::<E, Req, Chunk>);
+            }));
+
+        // add send-chunk
+        if self
+            .send_stream_chunks
+            .insert(
+                chunk_event_id,
+                CallbackWith::new(move |world: &mut World, (request_id, data): (u64, Vec<u8>)| {
+                    syscall(
+                        world,
+                        (chunk_event_id, request_id, data),
+                        send_stream_chunk::<E, Req, Chunk, C>,
+                    );
+                }),
+            )
+            .is_some()
+        {
+            panic!("stream was already registered");
+        }
+
+        // add send-stream-end
+        // - streams are ended with acks and rejections
+        if self
+            .send_responses
+            .entry(chunk_event_id)
+            .or_default()
+            .insert(
+                request_event_id,
+                CallbackWith::new(|world: &mut World, response: PendingResponse| {
+                    syscall(world, response, send_stream_end::<E, Req, Chunk>);
+                }),
+            )
+            .is_some()
+        {
+            panic!("stream was already registered");
+        }
+    }
+
+    pub(crate) fn register_server_request<Req: SimplenetEvent, Resp: SimplenetEvent, C: EventCodec>(
+        &mut self,
+        request_event_id: u16,
+    )
+    {
+        // add clear-request
+        self.clear_request_queues
+            .push(Callback::new(|world: &mut World| {
+                syscall(world, (), clear_request_queue::<E, Req, Resp>);
+            }));
+
+        // add send-request
+        if self
+            .send_server_requests
+            .insert(
+                request_event_id,
+                CallbackWith::new(move |world: &mut World, (token, data): (ServerRequestToken, Vec<u8>)| {
+                    syscall(
+                        world,
+                        (request_event_id, token, data),
+                        send_server_request::<E, Req, Resp, C>,
+                    );
+                }),
+            )
+            .is_some()
+        {
+            panic!("server request/response was already registered");
+        }
+    }
+
+    fn register_message_forwarder<F: 'static>(&mut self, callback: Callback<()>)
+    {
+        let type_id = TypeId::of::<F>();
+        if self.message_forwarders.iter().any(|(id, _)| *id == type_id) {
+            panic!("{} forwarding was already added", std::any::type_name::<F>());
+        }
+
+        self.message_forwarders.push((type_id, callback));
+    }
+
+    pub(crate) fn register_message_trigger<T: SimplenetEvent + Clone>(&mut self)
+    {
+        self.register_message_forwarder::<NetServerMsg<T>>(Callback::new(|world: &mut World| {
+            syscall(world, (), trigger_messages::<E, T>);
+        }));
+    }
+
+    pub(crate) fn register_message_mirror<T: SimplenetEvent + Clone>(&mut self)
+    {
+        self.register_message_forwarder::<FromServer<T>>(Callback::new(|world: &mut World| {
+            syscall(world, (), mirror_messages::<E, T>);
+        }));
+    }
+
+    pub(crate) fn clear_all(&self, world: &mut World, swap_buffers: bool)
     {
         // clear connection events
         syscall(world, (), clear_connection_queue::<E>);
 
+        // clear diagnostics
+        syscall(world, (), clear_diagnostic_queue::<E>);
+        syscall(world, (), clear_decode_error_queue::<E>);
+
         // clear messages
         for cb in self.clear_message_queues.iter() {
-            cb.clone().apply(world);
+            cb.call_with(swap_buffers).apply(world);
         }
 
         // clear responses
         for cb in self.clear_response_queues.iter() {
             cb.clone().apply(world);
         }
+
+        // clear server requests
+        // - dropping the tokens rejects the requests
+        for cb in self.clear_request_queues.iter() {
+            cb.clone().apply(world);
+        }
+    }
+
+    pub(crate) fn forward_all(&self, world: &mut World)
+    {
+        for (_, cb) in self.message_forwarders.iter() {
+            cb.clone().apply(world);
+        }
     }
 
     pub(crate) fn handle_disconnect(&self, world: &mut World)
     {
         tracing::debug!("clearing client queues on disconnect");
 
-        // clear messages
-        for cb in self.clear_message_queues.iter() {
+        // discard messages
+        for cb in self.clear_message_sessions.iter() {
             cb.clone().apply(world);
         }
 
@@ -210,6 +530,11 @@ impl<E: EventPack> EventQueueConnectorClient<E>
         for cb in self.reset_response_queues.iter() {
             cb.clone().apply(world);
         }
+
+        // discard server requests
+        for cb in self.clear_request_queues.iter() {
+            cb.clone().apply(world);
+        }
     }
 
     pub(crate) fn send_connection(&self, world: &mut World, counter: u32, report: ClientReport)
@@ -217,6 +542,16 @@ impl<E: EventPack> EventQueueConnectorClient<E>
         syscall(world, (counter, report), send_connection::<E>);
     }
 
+    pub(crate) fn send_schema_mismatch(&self, world: &mut World, mismatch: SchemaMismatch)
+    {
+        syscall(world, mismatch, send_schema_mismatch::<E>);
+    }
+
+    pub(crate) fn send_diagnostic(&self, world: &mut World, diagnostic: ClientDiagnostic)
+    {
+        syscall(world, diagnostic, send_diagnostic::<E>);
+    }
+
     pub(crate) fn send_message(&self, world: &mut World, message_event_id: u16, data: Vec<u8>)
     {
         let Some(cb) = self.send_messages.get(&message_event_id) else {
@@ -246,9 +581,35 @@ impl<E: EventPack> EventQueueConnectorClient<E>
             return;
         };
 
-        cb.call_with(PendingResponse { request_id, data })
+        cb.call_with(PendingResponse { response_event_id, request_id, data })
             .apply(world);
     }
+
+    pub(crate) fn send_stream_chunk(&self, world: &mut World, chunk_event_id: u16, request_id: u64, data: Vec<u8>)
+    {
+        let Some(cb) = self.send_stream_chunks.get(&chunk_event_id) else {
+            tracing::error!("tried to send stream chunk of unregistered chunk type");
+            return;
+        };
+
+        cb.call_with((request_id, data)).apply(world);
+    }
+
+    pub(crate) fn send_server_request(
+        &self,
+        world: &mut World,
+        request_event_id: u16,
+        token: ServerRequestToken,
+        data: Vec<u8>,
+    )
+    {
+        let Some(cb) = self.send_server_requests.get(&request_event_id) else {
+            tracing::error!("tried to send server request of unregistered request type");
+            return;
+        };
+
+        cb.call_with((token, data)).apply(world);
+    }
 }
 
 impl<E: EventPack> Default for EventQueueConnectorClient<E>
@@ -258,10 +619,15 @@ impl<E: EventPack> Default for EventQueueConnectorClient<E>
         Self {
             clear_message_queues: Vec::default(),
             clear_response_queues: Vec::default(),
+            clear_request_queues: Vec::default(),
+            clear_message_sessions: Vec::default(),
             reset_response_queues: Vec::default(),
             send_messages: HashMap::default(),
             send_responses: HashMap::default(),
-            phantom: PhantomData::default(),
+            send_stream_chunks: HashMap::default(),
+            send_server_requests: HashMap::default(),
+            message_forwarders: Vec::default(),
+            phantom: PhantomData,
         }
     }
 }
@@ -13,6 +13,8 @@ pub(crate) struct ClientConnectionQueue<E: EventPack>
     /// Includes event counter for use in synchronizing with [`EventClient::send`] the first time a
     /// `ClientReport::Connected` is iterated over.
     queue: Vec<(u32, ClientReport)>,
+    /// Reports that the server's event schema doesn't match the client's.
+    mismatches: Vec<SchemaMismatch>,
     phantom: PhantomData<E>,
 }
 
@@ -21,6 +23,7 @@ impl<E: EventPack> ClientConnectionQueue<E>
     pub(crate) fn clear(&mut self)
     {
         self.queue.clear();
+        self.mismatches.clear();
     }
 
     pub(crate) fn send(&mut self, counter: u32, report: ClientReport)
@@ -32,48 +35,142 @@ impl<E: EventPack> ClientConnectionQueue<E>
     {
         self.queue.iter()
     }
+
+    pub(crate) fn send_schema_mismatch(&mut self, mismatch: SchemaMismatch)
+    {
+        self.mismatches.push(mismatch);
+    }
+
+    pub(crate) fn iter_schema_mismatches(&self) -> impl Iterator<Item = &SchemaMismatch> + '_
+    {
+        self.mismatches.iter()
+    }
 }
 
 impl<E: EventPack> Default for ClientConnectionQueue<E>
 {
     fn default() -> Self
     {
-        Self { queue: Vec::default(), phantom: PhantomData::default() }
+        Self {
+            queue: Vec::default(),
+            mismatches: Vec::default(),
+            phantom: PhantomData,
+        }
     }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
 #[derive(Resource)]
-pub(crate) struct ClientMessageQueue<E: EventPack, T: SimplenetEvent>
+pub(crate) struct ClientDiagnosticQueue<E: EventPack>
 {
-    queue: Vec<T>,
+    queue: Vec<ClientDiagnostic>,
     phantom: PhantomData<E>,
 }
 
-impl<E: EventPack, T: SimplenetEvent> ClientMessageQueue<E, T>
+impl<E: EventPack> ClientDiagnosticQueue<E>
 {
     pub(crate) fn clear(&mut self)
     {
         self.queue.clear();
     }
 
+    pub(crate) fn send(&mut self, diagnostic: ClientDiagnostic)
+    {
+        self.queue.push(diagnostic);
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &ClientDiagnostic> + '_
+    {
+        self.queue.iter()
+    }
+}
+
+impl<E: EventPack> Default for ClientDiagnosticQueue<E>
+{
+    fn default() -> Self
+    {
+        Self { queue: Vec::default(), phantom: PhantomData }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Resource)]
+pub(crate) struct ClientDecodeErrorQueue<E: EventPack>
+{
+    queue: Vec<DecodeError>,
+    phantom: PhantomData<E>,
+}
+
+impl<E: EventPack> ClientDecodeErrorQueue<E>
+{
+    pub(crate) fn clear(&mut self)
+    {
+        self.queue.clear();
+    }
+
+    pub(crate) fn send(&mut self, error: DecodeError)
+    {
+        self.queue.push(error);
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &DecodeError> + '_
+    {
+        self.queue.iter()
+    }
+}
+
+impl<E: EventPack> Default for ClientDecodeErrorQueue<E>
+{
+    fn default() -> Self
+    {
+        Self { queue: Vec::default(), phantom: PhantomData }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Resource)]
+pub(crate) struct ClientMessageQueue<E: EventPack, T: SimplenetEvent>
+{
+    queue: EventBuffer<T>,
+    phantom: PhantomData<E>,
+}
+
+impl<E: EventPack, T: SimplenetEvent> ClientMessageQueue<E, T>
+{
+    pub(crate) fn clear(&mut self, swap: bool)
+    {
+        self.queue.update(swap);
+    }
+
+    pub(crate) fn clear_session(&mut self)
+    {
+        self.queue.discard(|_| true);
+    }
+
     pub(crate) fn send(&mut self, message: T)
     {
-        self.queue.push(message);
+        self.queue.send(message);
     }
 
     pub(crate) fn iter(&self) -> impl Iterator<Item = &T> + '_
     {
         self.queue.iter()
     }
+
+    pub(crate) fn read(&self, cursor: &mut usize) -> impl Iterator<Item = &T> + '_
+    {
+        self.queue.read(cursor)
+    }
 }
 
 impl<E: EventPack, T: SimplenetEvent> Default for ClientMessageQueue<E, T>
 {
     fn default() -> Self
     {
-        Self { queue: Vec::default(), phantom: PhantomData::default() }
+        Self { queue: EventBuffer::default(), phantom: PhantomData }
     }
 }
 
@@ -122,7 +219,93 @@ impl<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent> Default for Client
 {
     fn default() -> Self
     {
-        Self { queue: Vec::default(), phantom: PhantomData::default() }
+        Self { queue: Vec::default(), phantom: PhantomData }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Resource)]
+pub(crate) struct ClientRequestQueue<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>
+{
+    queue: Vec<(ServerRequestToken, Req)>,
+    phantom: PhantomData<(E, Resp)>,
+}
+
+impl<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent> ClientRequestQueue<E, Req, Resp>
+{
+    pub(crate) fn clear(&mut self)
+    {
+        self.queue.clear();
+    }
+
+    pub(crate) fn send(&mut self, token: ServerRequestToken, request: Req)
+    {
+        self.queue.push((token, request));
+    }
+
+    pub(crate) fn drain(&mut self) -> impl Iterator<Item = (ServerRequestToken, Req)> + '_
+    {
+        self.queue.drain(..)
+    }
+}
+
+impl<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent> Default for ClientRequestQueue<E, Req, Resp>
+{
+    fn default() -> Self
+    {
+        Self { queue: Vec::default(), phantom: PhantomData }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Resource)]
+pub(crate) struct ClientStreamQueue<E: EventPack, Req: SimplenetEvent, Chunk: SimplenetEvent>
+{
+    queue: Vec<StreamResponse<Chunk>>,
+    phantom: PhantomData<(E, Req)>,
+}
+
+impl<E: EventPack, Req: SimplenetEvent, Chunk: SimplenetEvent> ClientStreamQueue<E, Req, Chunk>
+{
+    pub(crate) fn clear(&mut self)
+    {
+        self.queue.clear();
+    }
+
+    /// Discards chunks and replaces `Done` with `ResponseLost`.
+    ///
+    /// Streams that are still open will be resolved as `ResponseLost` by the internal client.
+    pub(crate) fn reset(&mut self)
+    {
+        self.queue
+            .retain(|response| !matches!(response, StreamResponse::Chunk(..)));
+        for response in self.queue.iter_mut() {
+            let StreamResponse::Done(id) = response else {
+                continue;
+            };
+            tracing::warn!("'losing' server stream older than a recent disconnect");
+            *response = StreamResponse::<Chunk>::ResponseLost(*id);
+        }
+    }
+
+    pub(crate) fn send(&mut self, response: StreamResponse<Chunk>)
+    {
+        self.queue.push(response);
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &StreamResponse<Chunk>> + '_
+    {
+        self.queue.iter()
+    }
+}
+
+impl<E: EventPack, Req: SimplenetEvent, Chunk: SimplenetEvent> Default for ClientStreamQueue<E, Req, Chunk>
+{
+    fn default() -> Self
+    {
+        Self { queue: Vec::default(), phantom: PhantomData }
     }
 }
 
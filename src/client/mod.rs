@@ -1,17 +1,34 @@
 //module tree
 mod app_ext;
+mod connection_state;
+mod diagnostics;
+mod event_bridge;
 mod event_client;
 mod event_client_core;
 mod event_queue_connector;
 mod event_queues;
 mod readers;
+mod server_request_token;
 mod server_response;
+#[cfg(feature = "states")]
+#[cfg_attr(docsrs, doc(cfg(feature = "states")))]
+mod states;
+mod stream_response;
+mod triggers;
 
 //API exports
 pub use crate::client::app_ext::*;
+pub use crate::client::connection_state::*;
+pub use crate::client::diagnostics::*;
+pub use crate::client::event_bridge::*;
 pub use crate::client::event_client::*;
 pub(crate) use crate::client::event_client_core::*;
 pub(crate) use crate::client::event_queue_connector::*;
 pub(crate) use crate::client::event_queues::*;
 pub use crate::client::readers::*;
+pub use crate::client::server_request_token::*;
 pub use crate::client::server_response::*;
+#[cfg(feature = "states")]
+pub use crate::client::states::*;
+pub use crate::client::stream_response::*;
+pub use crate::client::triggers::*;
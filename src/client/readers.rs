@@ -24,6 +24,54 @@ impl<'w, E: EventPack> ClientConnectionReader<'w, E>
             report
         })
     }
+
+    /// Iterates reports that the server's event schema doesn't match the client's.
+    ///
+    /// Schemas are exchanged when the client connects, so a mismatch will be reported shortly after
+    /// `ClientReport::Connected`. The client can exchange events with the server until then. Servers with
+    /// [`SchemaPolicy::Disconnect`] don't exchange events with the client until its schema is verified.
+    pub fn schema_mismatches(&self) -> impl Iterator<Item = &SchemaMismatch> + '_
+    {
+        self.events.iter_schema_mismatches()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Client reader for [`ClientDiagnostics`](ClientDiagnostic).
+#[derive(SystemParam)]
+pub struct ClientDiagnosticReader<'w, E: EventPack>
+{
+    events: Res<'w, ClientDiagnosticQueue<E>>,
+}
+
+impl<'w, E: EventPack> ClientDiagnosticReader<'w, E>
+{
+    /// Iterates the available diagnostics.
+    pub fn iter(&self) -> impl Iterator<Item = &ClientDiagnostic> + '_
+    {
+        self.events.iter()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Client reader for server messages and responses that failed to deserialize.
+///
+/// Responses that fail to deserialize resolve as [`ServerResponse::Malformed`].
+#[derive(SystemParam)]
+pub struct ClientDecodeErrorReader<'w, E: EventPack>
+{
+    events: Res<'w, ClientDecodeErrorQueue<E>>,
+}
+
+impl<'w, E: EventPack> ClientDecodeErrorReader<'w, E>
+{
+    /// Iterates the available decode errors.
+    pub fn iter(&self) -> impl Iterator<Item = &DecodeError> + '_
+    {
+        self.events.iter()
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -46,6 +94,39 @@ impl<'w, E: EventPack, T: SimplenetEvent> ClientMessageReader<'w, E, T>
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Client reader for server messages that tracks which messages each system has read.
+///
+/// [`ClientMessageReader`] only sees messages received in the current tick. This reader works like
+/// `bevy_ecs::event::EventReader` instead: each system sees each message exactly once, even if it runs in a
+/// schedule that skips ticks or runs several times per tick (e.g. `FixedUpdate`).
+///
+/// Messages are buffered for two ticks (or two fixed-update cycles if `bevy_time` is driving fixed updates), so a
+/// system that doesn't run for longer than that will miss messages. Messages from before a disconnect are
+/// discarded.
+#[derive(SystemParam)]
+pub struct ClientMessageCursor<'w, 's, E: EventPack, T: SimplenetEvent>
+{
+    cursor: Local<'s, usize>,
+    events: Res<'w, ClientMessageQueue<E, T>>,
+}
+
+impl<'w, 's, E: EventPack, T: SimplenetEvent> ClientMessageCursor<'w, 's, E, T>
+{
+    /// Iterates server messages this system hasn't read yet.
+    pub fn read(&mut self) -> impl Iterator<Item = &T> + '_
+    {
+        self.events.read(&mut self.cursor)
+    }
+
+    /// Marks all buffered messages as read.
+    pub fn clear(&mut self)
+    {
+        self.read().for_each(|_| ());
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Client reader for server responses to client requests.
 #[derive(SystemParam)]
 pub struct ClientResponseReader<'w, E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>
@@ -63,3 +144,50 @@ impl<'w, E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent> ClientResponse
 }
 
 //-------------------------------------------------------------------------------------------------------------------
+
+/// Client reader for server streams for client stream requests.
+#[derive(SystemParam)]
+pub struct ClientStreamReader<'w, E: EventPack, Req: SimplenetEvent, Chunk: SimplenetEvent>
+{
+    events: Res<'w, ClientStreamQueue<E, Req, Chunk>>,
+}
+
+impl<'w, E: EventPack, Req: SimplenetEvent, Chunk: SimplenetEvent> ClientStreamReader<'w, E, Req, Chunk>
+{
+    /// Iterates the available stream events for all requests.
+    pub fn iter(&self) -> impl Iterator<Item = &StreamResponse<Chunk>> + '_
+    {
+        self.events.iter()
+    }
+
+    /// Iterates the available stream events for one request.
+    pub fn iter_request(&self, request_id: u64) -> impl Iterator<Item = &StreamResponse<Chunk>> + '_
+    {
+        self.events
+            .iter()
+            .filter(move |response| response.request_id() == request_id)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Client source for server requests.
+///
+/// Requests can only be drained here, since we expect the user to do something with request tokens. Requests
+/// that aren't drained before the next refresh are rejected.
+#[derive(SystemParam)]
+pub struct ClientRequestSource<'w, E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>
+{
+    events: ResMut<'w, ClientRequestQueue<E, Req, Resp>>,
+}
+
+impl<'w, E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent> ClientRequestSource<'w, E, Req, Resp>
+{
+    /// Drains all available server requests.
+    pub fn drain(&mut self) -> impl Iterator<Item = (ServerRequestToken, Req)> + '_
+    {
+        self.events.drain()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,72 @@
+use crossbeam::channel::Sender;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Token for responding to a server request.
+///
+/// Use [`EventClient::respond`](crate::EventClient::respond), [`EventClient::ack`](crate::EventClient::ack), or
+/// [`EventClient::reject`](crate::EventClient::reject) to resolve the request. Dropping the token rejects the
+/// request.
+#[derive(Debug)]
+pub struct ServerRequestToken
+{
+    request_id: u64,
+    response_event_id: u16,
+    session: u64,
+    /// Used to reject the request on drop.
+    /// [ (request id, response event id, session) ]
+    dropper: Option<Sender<(u64, u16, u64)>>,
+}
+
+impl ServerRequestToken
+{
+    pub(crate) fn new(
+        request_id: u64,
+        response_event_id: u16,
+        session: u64,
+        dropper: Sender<(u64, u16, u64)>,
+    ) -> Self
+    {
+        Self {
+            request_id,
+            response_event_id,
+            session,
+            dropper: Some(dropper),
+        }
+    }
+
+    /// Gets the server's id for this request.
+    pub fn request_id(&self) -> u64
+    {
+        self.request_id
+    }
+
+    pub(crate) fn response_event_id(&self) -> u16
+    {
+        self.response_event_id
+    }
+
+    pub(crate) fn session(&self) -> u64
+    {
+        self.session
+    }
+
+    /// Marks the request as resolved so it won't be rejected on drop.
+    pub(crate) fn consume(mut self)
+    {
+        self.dropper = None;
+    }
+}
+
+impl Drop for ServerRequestToken
+{
+    fn drop(&mut self)
+    {
+        let Some(dropper) = self.dropper.take() else {
+            return;
+        };
+        let _ = dropper.send((self.request_id, self.response_event_id, self.session));
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
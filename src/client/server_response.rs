@@ -15,6 +15,14 @@ pub enum ServerResponse<T: SimplenetEvent>
     SendFailed(u64),
     /// The server received a request but the client failed to receive a response.
     ResponseLost(u64),
+    /// The server sent a response that doesn't match the request, or that failed to deserialize.
+    ///
+    /// See [`ClientDiagnostic::MalformedResponse`] and [`ClientDecodeErrorReader`].
+    Malformed(u64),
+    /// The request's timeout elapsed before the server responded. A late response will be discarded.
+    TimedOut(u64),
+    /// The request was cancelled with [`EventClient::cancel`]. A late response will be discarded.
+    Cancelled(u64),
 }
 
 impl<T: SimplenetEvent> ServerResponse<T>
@@ -37,6 +45,9 @@ impl<T: SimplenetEvent> ServerResponse<T>
             Self::Reject(request_id) => *request_id,
             Self::SendFailed(request_id) => *request_id,
             Self::ResponseLost(request_id) => *request_id,
+            Self::Malformed(request_id) => *request_id,
+            Self::TimedOut(request_id) => *request_id,
+            Self::Cancelled(request_id) => *request_id,
         }
     }
 }
@@ -53,6 +64,9 @@ impl<T: SimplenetEvent + Eq + PartialEq> PartialEq for ServerResponse<T>
             (Self::Reject(l0), Self::Reject(r0)) => l0 == r0,
             (Self::SendFailed(l0), Self::SendFailed(r0)) => l0 == r0,
             (Self::ResponseLost(l0), Self::ResponseLost(r0)) => l0 == r0,
+            (Self::Malformed(l0), Self::Malformed(r0)) => l0 == r0,
+            (Self::TimedOut(l0), Self::TimedOut(r0)) => l0 == r0,
+            (Self::Cancelled(l0), Self::Cancelled(r0)) => l0 == r0,
             _ => false,
         }
     }
@@ -0,0 +1,181 @@
+use std::convert::Infallible;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use bevy_app::{App, First, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy_simplenet::ClientReport;
+use bevy_state::prelude::*;
+use bevy_state::state::FreelyMutableState;
+
+use crate::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Built-in state for [`SimplenetClientStatesPlugin`].
+///
+/// Use `SimplenetClientStatesPlugin::<E, NetworkState<E>>::default()` to drive it from client `E`.
+#[derive(Default)]
+pub enum NetworkState<E: EventPack>
+{
+    /// The client is not connected.
+    #[default]
+    Disconnected,
+    /// The client is connected.
+    Connected,
+    #[doc(hidden)]
+    _Phantom(Infallible, PhantomData<E>),
+}
+
+impl<E: EventPack> Clone for NetworkState<E>
+{
+    fn clone(&self) -> Self
+    {
+        match self {
+            Self::Disconnected => Self::Disconnected,
+            Self::Connected => Self::Connected,
+            Self::_Phantom(never, _) => match *never {},
+        }
+    }
+}
+
+impl<E: EventPack> PartialEq for NetworkState<E>
+{
+    fn eq(&self, other: &Self) -> bool
+    {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+impl<E: EventPack> Eq for NetworkState<E> {}
+
+impl<E: EventPack> Hash for NetworkState<E>
+{
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H)
+    {
+        std::mem::discriminant(self).hash(state);
+    }
+}
+
+impl<E: EventPack> Debug for NetworkState<E>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self {
+            Self::Disconnected => f.write_str("Disconnected"),
+            Self::Connected => f.write_str("Connected"),
+            Self::_Phantom(never, _) => match *never {},
+        }
+    }
+}
+
+impl<E: EventPack> States for NetworkState<E> {}
+impl<E: EventPack> FreelyMutableState for NetworkState<E> {}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Plugin that drives a [`States`] type from the connection reports of client `E`.
+///
+/// When the client connects, `NextState<S>` is set to the connected state. When the client disconnects, closes,
+/// or dies, it is set to the disconnected state.
+///
+/// The state is updated right after [`RefreshSet`] in the schedule the client is refreshed in (`First` by
+/// default, see [`Self::in_schedule`]). Bevy applies the transition in `StateTransition` after `PreUpdate`, so
+/// `OnExit`/`OnEnter` systems run after [`RefreshSet`] and before connection and message handlers in `Update`.
+///
+/// If several reports arrive in one refresh, only the last one is applied. For example, if the client disconnects
+/// and reconnects in one refresh, then the state stays connected and `OnExit`/`OnEnter` systems don't run. The
+/// state is still set, so bevy applies an identity transition that can be handled with `OnTransition` (or by
+/// reading `StateTransitionEvent`).
+///
+/// The plugin does not read `ClientReport::Connected`, so `OnEnter` systems for the connected state must read
+/// the report with [`ClientConnectionReader`] before they can send messages. Otherwise sending will fail with
+/// [`SendError::PendingConnect`].
+///
+/// The state is initialized with `init_state` if it doesn't exist. Requires bevy's `StatesPlugin`.
+pub struct SimplenetClientStatesPlugin<E: EventPack, S: FreelyMutableState + FromWorld>
+{
+    connected: S,
+    disconnected: S,
+    schedule: InternedScheduleLabel,
+    phantom: PhantomData<E>,
+}
+
+impl<E: EventPack, S: FreelyMutableState + FromWorld> SimplenetClientStatesPlugin<E, S>
+{
+    /// Makes a new plugin that maps connection reports to the given states.
+    pub fn new(connected: S, disconnected: S) -> Self
+    {
+        Self {
+            connected,
+            disconnected,
+            schedule: First.intern(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Sets the schedule the client is refreshed in.
+    ///
+    /// Use this if the client was inserted with
+    /// [`insert_simplenet_client_in`](SimplenetClientEventAppExt::insert_simplenet_client_in).
+    pub fn in_schedule(mut self, schedule: impl ScheduleLabel) -> Self
+    {
+        self.schedule = schedule.intern();
+        self
+    }
+}
+
+impl<E: EventPack> Default for SimplenetClientStatesPlugin<E, NetworkState<E>>
+{
+    fn default() -> Self
+    {
+        Self::new(NetworkState::Connected, NetworkState::Disconnected)
+    }
+}
+
+impl<E: EventPack, S: FreelyMutableState + FromWorld> Plugin for SimplenetClientStatesPlugin<E, S>
+{
+    fn build(&self, app: &mut App)
+    {
+        app.init_state::<S>();
+
+        let connected = self.connected.clone();
+        let disconnected = self.disconnected.clone();
+        app.add_systems(
+            self.schedule,
+            (move |world: &mut World| update_client_state::<E, S>(world, &connected, &disconnected))
+                .after(RefreshSet)
+                .run_if(resource_exists::<EventClientCore<E>>),
+        );
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Sets the state from the last connection report in the most recent refresh.
+///
+/// Reports are read from the raw queue so the client's pending connect is not cleared.
+fn update_client_state<E: EventPack, S: FreelyMutableState>(world: &mut World, connected: &S, disconnected: &S)
+{
+    let current = world.resource::<State<S>>().get();
+    let mut changed = false;
+    let mut last = None;
+    for (_, report) in world.resource::<ClientConnectionQueue<E>>().iter() {
+        let target = match report {
+            ClientReport::Connected => connected,
+            _ => disconnected,
+        };
+        changed |= target != current;
+        last = Some(target);
+    }
+
+    let Some(last) = last.filter(|_| changed).cloned() else {
+        return;
+    };
+
+    world.resource_mut::<NextState<S>>().set(last);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
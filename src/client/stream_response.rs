@@ -0,0 +1,84 @@
+use crate::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Server stream event for a client stream request.
+///
+/// A stream emits zero or more [`StreamResponse::Chunk`]s followed by exactly one terminal event.
+pub enum StreamResponse<T: SimplenetEvent>
+{
+    /// Chunk from the server.
+    Chunk(T, u64),
+    /// The server ended the stream. No more chunks will be sent.
+    Done(u64),
+    /// The server failed the stream. No more chunks will be sent.
+    Error(u64),
+    /// Sending a request failed.
+    SendFailed(u64),
+    /// The server received a request but the client failed to receive the end of the stream.
+    ResponseLost(u64),
+    /// The server ended the stream with a response that doesn't match the request.
+    ///
+    /// See [`ClientDiagnostic::MalformedResponse`]. Chunks that fail to deserialize don't end the stream, they
+    /// are reported by [`ClientDecodeErrorReader`].
+    Malformed(u64),
+    /// The request's timeout elapsed before the stream ended. Late chunks will be discarded.
+    TimedOut(u64),
+    /// The request was cancelled with [`EventClient::cancel`]. Late chunks will be discarded.
+    Cancelled(u64),
+}
+
+impl<T: SimplenetEvent> StreamResponse<T>
+{
+    /// Accesses the internal chunk if self is [`StreamResponse::Chunk`].
+    pub fn chunk(&self) -> Option<&T>
+    {
+        match self {
+            Self::Chunk(chunk, _) => Some(chunk),
+            _ => None,
+        }
+    }
+
+    /// Assesses the stream's original request id.
+    pub fn request_id(&self) -> u64
+    {
+        match self {
+            Self::Chunk(_, request_id) => *request_id,
+            Self::Done(request_id) => *request_id,
+            Self::Error(request_id) => *request_id,
+            Self::SendFailed(request_id) => *request_id,
+            Self::ResponseLost(request_id) => *request_id,
+            Self::Malformed(request_id) => *request_id,
+            Self::TimedOut(request_id) => *request_id,
+            Self::Cancelled(request_id) => *request_id,
+        }
+    }
+
+    /// Checks if this event ends the stream.
+    pub fn is_terminal(&self) -> bool
+    {
+        !matches!(self, Self::Chunk(..))
+    }
+}
+
+impl<T: SimplenetEvent + Eq + PartialEq> Eq for StreamResponse<T> {}
+
+impl<T: SimplenetEvent + Eq + PartialEq> PartialEq for StreamResponse<T>
+{
+    fn eq(&self, other: &Self) -> bool
+    {
+        match (self, other) {
+            (Self::Chunk(l0, l1), Self::Chunk(r0, r1)) => l0 == r0 && l1 == r1,
+            (Self::Done(l0), Self::Done(r0)) => l0 == r0,
+            (Self::Error(l0), Self::Error(r0)) => l0 == r0,
+            (Self::SendFailed(l0), Self::SendFailed(r0)) => l0 == r0,
+            (Self::ResponseLost(l0), Self::ResponseLost(r0)) => l0 == r0,
+            (Self::Malformed(l0), Self::Malformed(r0)) => l0 == r0,
+            (Self::TimedOut(l0), Self::TimedOut(r0)) => l0 == r0,
+            (Self::Cancelled(l0), Self::Cancelled(r0)) => l0 == r0,
+            _ => false,
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
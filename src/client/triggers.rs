@@ -0,0 +1,17 @@
+use bevy_ecs::prelude::*;
+
+use crate::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Observer event triggered on the client for each server message of type `T`.
+///
+/// Enable with [`SimplenetClientEventAppExt::add_simplenet_client_trigger`].
+#[derive(Event, Debug, Clone)]
+pub struct NetServerMsg<T: SimplenetEvent>
+{
+    /// The message.
+    pub message: T,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
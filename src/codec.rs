@@ -0,0 +1,207 @@
+use std::fmt::{Debug, Display};
+use std::marker::PhantomData;
+
+use bincode::Options;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Error emitted by an [`EventCodec`].
+pub struct CodecError(Box<dyn std::error::Error + Send + Sync + 'static>);
+
+impl CodecError
+{
+    /// Makes a new codec error.
+    pub fn new(err: impl Into<Box<dyn std::error::Error + Send + Sync + 'static>>) -> Self
+    {
+        Self(err.into())
+    }
+}
+
+impl Debug for CodecError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl Display for CodecError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl std::error::Error for CodecError
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)>
+    {
+        Some(&*self.0)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Reports a received event that failed to deserialize.
+///
+/// See `ServerDecodeErrorReader` and `ClientDecodeErrorReader`.
+#[derive(Debug)]
+pub struct DecodeError
+{
+    /// The event's wire id.
+    pub event_id: u16,
+    /// The event's type name, from [`std::any::type_name`].
+    pub type_name: &'static str,
+    /// The codec error.
+    pub error: CodecError,
+}
+
+impl DecodeError
+{
+    pub(crate) fn new<T: 'static>(event_id: u16, error: CodecError) -> Self
+    {
+        Self { event_id, type_name: std::any::type_name::<T>(), error }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Serialization format for events.
+///
+/// See [`EventPack::encode`](crate::EventPack::encode).
+pub trait EventCodec: 'static
+{
+    /// Serializes an event.
+    fn encode<T: Serialize>(event: &T) -> Result<Vec<u8>, CodecError>;
+
+    /// Deserializes an event.
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CodecError>;
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Codec that uses `bincode` with `bincode::DefaultOptions`.
+///
+/// This is the default codec for all [`EventPacks`](crate::EventPack).
+#[derive(Debug, Copy, Clone, Default)]
+pub struct BincodeCodec;
+
+impl EventCodec for BincodeCodec
+{
+    fn encode<T: Serialize>(event: &T) -> Result<Vec<u8>, CodecError>
+    {
+        bincode::DefaultOptions::new()
+            .serialize(event)
+            .map_err(CodecError::new)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CodecError>
+    {
+        bincode::DefaultOptions::new()
+            .deserialize(bytes)
+            .map_err(CodecError::new)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Flag byte for payloads that [`Lz4Codec`] sent uncompressed.
+const LZ4_RAW: u8 = 0;
+/// Flag byte for payloads that [`Lz4Codec`] compressed.
+const LZ4_COMPRESSED: u8 = 1;
+
+/// LZ4 can't compress data by more than this ratio, so larger claimed sizes are rejected before allocating.
+const LZ4_MAX_RATIO: usize = 255;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Codec that compresses payloads from an inner codec `C` with LZ4.
+///
+/// Payloads are only compressed if their encoded size is at least `THRESHOLD` bytes and compression makes them
+/// smaller. A flag byte at the start of each payload records whether it was compressed, so small events only cost
+/// one extra byte.
+///
+/// Use this for a single event type with [`register_simplenet_server_message_with_codec`] and friends, or for a
+/// whole channel by overriding [`EventPack::encode`] and [`EventPack::decode`].
+/**
+// compress world snapshots larger than 4KB
+app.register_simplenet_client_message_with_codec::<MyChannel, WorldSnapshot, Lz4Codec<BincodeCodec, 4096>>();
+*/
+///
+/// [`register_simplenet_server_message_with_codec`]: crate::SimplenetEventAppExt::register_simplenet_server_message_with_codec
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Lz4Codec<C: EventCodec = BincodeCodec, const THRESHOLD: usize = 1024>(PhantomData<C>);
+
+impl<C: EventCodec, const THRESHOLD: usize> EventCodec for Lz4Codec<C, THRESHOLD>
+{
+    fn encode<T: Serialize>(event: &T) -> Result<Vec<u8>, CodecError>
+    {
+        let raw = C::encode(event)?;
+
+        if raw.len() >= THRESHOLD {
+            let compressed = lz4_flex::block::compress_prepend_size(&raw);
+            if compressed.len() < raw.len() {
+                let mut data = Vec::with_capacity(compressed.len() + 1);
+                data.push(LZ4_COMPRESSED);
+                data.extend_from_slice(&compressed);
+                return Ok(data);
+            }
+        }
+
+        let mut data = Vec::with_capacity(raw.len() + 1);
+        data.push(LZ4_RAW);
+        data.extend_from_slice(&raw);
+        Ok(data)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CodecError>
+    {
+        let Some((flag, payload)) = bytes.split_first() else {
+            return Err(CodecError::new("lz4 payload is missing its flag byte"));
+        };
+
+        match *flag {
+            LZ4_RAW => C::decode(payload),
+            LZ4_COMPRESSED => {
+                let (size, compressed) = lz4_flex::block::uncompressed_size(payload).map_err(CodecError::new)?;
+                if size > compressed.len().saturating_mul(LZ4_MAX_RATIO) {
+                    return Err(CodecError::new("lz4 payload claims an impossible decompressed size"));
+                }
+
+                let mut raw = vec![0u8; size];
+                let len = lz4_flex::block::decompress_into(compressed, &mut raw).map_err(CodecError::new)?;
+                if len != size {
+                    return Err(CodecError::new("lz4 payload decompressed to the wrong size"));
+                }
+
+                C::decode(&raw)
+            }
+            _ => Err(CodecError::new("lz4 payload has an unknown flag byte")),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Codec that uses an [`EventPack`]'s channel-wide codec.
+pub(crate) struct ChannelCodec<E: EventPack>(PhantomData<E>);
+
+impl<E: EventPack> EventCodec for ChannelCodec<E>
+{
+    fn encode<T: Serialize>(event: &T) -> Result<Vec<u8>, CodecError>
+    {
+        E::encode(event)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CodecError>
+    {
+        E::decode(bytes)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -1,9 +1,10 @@
 use std::fmt::Debug;
 use std::marker::PhantomData;
 
-use bevy_app::App;
+use bevy_app::{App, First};
 use bevy_ecs::prelude::*;
 use bevy_simplenet::ChannelPack;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, Bytes};
 
@@ -22,17 +23,24 @@ fn setup_simplenet_event_framwork<E: EventPack>(app: &mut App)
     // - this can only be done from within this crate
     app.init_resource::<EventRegistry<E>>();
 
+    // buffered events must be refreshed before bevy's event update system resets its update signal
+    app.configure_sets(First, RefreshSet.before(bevy_ecs::event::EventUpdates));
+
     // prepare internals
     #[cfg(feature = "server")]
     {
         app.init_resource::<EventQueueConnectorServer<E>>();
         app.init_resource::<ServerConnectionQueue<E>>();
+        app.init_resource::<ServerDecodeErrorQueue<E>>();
+        app.init_resource::<ServerCancelQueue<E>>();
     }
 
     #[cfg(feature = "client")]
     {
         app.init_resource::<EventQueueConnectorClient<E>>();
         app.init_resource::<ClientConnectionQueue<E>>();
+        app.init_resource::<ClientDiagnosticQueue<E>>();
+        app.init_resource::<ClientDecodeErrorQueue<E>>();
     }
 }
 
@@ -54,6 +62,38 @@ pub struct InternalEvent
 pub trait EventPack: Clone + Debug + Send + Sync + 'static
 {
     type ConnectMsg: Clone + Debug + Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static;
+
+    /// Serializes an event for this channel.
+    ///
+    /// Defaults to [`BincodeCodec`]. Override this and [`Self::decode`] to use a different [`EventCodec`].
+    /**
+    impl EventPack for MyChannel
+    {
+        type ConnectMsg = ();
+
+        fn encode<T: Serialize>(event: &T) -> Result<Vec<u8>, CodecError>
+        {
+            MyJsonCodec::encode(event)
+        }
+
+        fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CodecError>
+        {
+            MyJsonCodec::decode(bytes)
+        }
+    }
+    */
+    fn encode<T: Serialize>(event: &T) -> Result<Vec<u8>, CodecError>
+    {
+        BincodeCodec::encode(event)
+    }
+
+    /// Deserializes an event for this channel.
+    ///
+    /// Defaults to [`BincodeCodec`]. See [`Self::encode`].
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CodecError>
+    {
+        BincodeCodec::decode(bytes)
+    }
 }
 
 /// Wrapper struct for carrying type information when constructing clients and servers.
@@ -75,7 +115,7 @@ impl<E: EventPack> Default for EventWrapper<E>
 {
     fn default() -> Self
     {
-        Self(PhantomData::default())
+        Self(PhantomData)
     }
 }
 
@@ -87,8 +127,37 @@ impl<E: EventPack> Default for EventWrapper<E>
 /**
 #[derive(SimplenetEvent, Serialize, Deserialize)]
 struct MyEvent(usize);
+
+#[derive(SimplenetEvent, Serialize, Deserialize)]
+#[simplenet(id = "inventory.move")]
+struct MyNamedEvent(usize);
+
+#[derive(SimplenetEvent, Serialize, Deserialize)]
+#[simplenet(id = 1234)]
+struct MyPinnedEvent(usize);
 */
-pub trait SimplenetEvent: Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static {}
+pub trait SimplenetEvent: Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static
+{
+    /// Stable name used to derive this event's id.
+    ///
+    /// If `None`, then the event id will be assigned in registration order. Otherwise the event id will be derived
+    /// from a hash of the name, so registration order doesn't matter for this event. Registering two events with
+    /// the same name, or with names whose ids collide, will panic.
+    ///
+    /// Named ids are folded into a 15-bit space (32768 ids), so hash collisions become likely once an app has a
+    /// few hundred named events. Use [`Self::EVENT_ID`] to pin an event to an explicit id if its name collides.
+    ///
+    /// Set this with `#[simplenet(id = "my.event")]`, or `#[simplenet(stable_id)]` to use the type's module path.
+    const EVENT_NAME: Option<&'static str> = None;
+
+    /// Explicit stable id for this event. Takes precedence over [`Self::EVENT_NAME`].
+    ///
+    /// The id must be less than `0x8000`. It shares the id space of named events, so registering two events with
+    /// the same explicit id, or with an explicit id that matches a named event's id, will panic.
+    ///
+    /// Set this with `#[simplenet(id = 1234)]`.
+    const EVENT_ID: Option<u16> = None;
+}
 
 impl SimplenetEvent for () {}
 
@@ -104,43 +173,138 @@ pub trait SimplenetEventAppExt
 {
     /// Registers a client-sent message event.
     ///
-    /// Server and client binaries must register events in the same order.
+    /// Server and client binaries must register events in the same order, except for events with a
+    /// [`SimplenetEvent::EVENT_NAME`].
     fn register_simplenet_client_message<E: EventPack, T: SimplenetEvent>(&mut self) -> &mut Self;
 
+    /// Registers a client-sent message event that is serialized with codec `C` instead of the channel's codec.
+    ///
+    /// An event type can only be registered with one codec.
+    fn register_simplenet_client_message_with_codec<E: EventPack, T: SimplenetEvent, C: EventCodec>(
+        &mut self,
+    ) -> &mut Self;
+
     /// Registers a server-sent message event.
     ///
-    /// Server and client binaries must register events in the same order.
+    /// Server and client binaries must register events in the same order, except for events with a
+    /// [`SimplenetEvent::EVENT_NAME`].
     fn register_simplenet_server_message<E: EventPack, T: SimplenetEvent>(&mut self) -> &mut Self;
 
+    /// Registers a server-sent message event that is serialized with codec `C` instead of the channel's codec.
+    ///
+    /// An event type can only be registered with one codec.
+    fn register_simplenet_server_message_with_codec<E: EventPack, T: SimplenetEvent, C: EventCodec>(
+        &mut self,
+    ) -> &mut Self;
+
     /// Registers a request-response event.
     ///
-    /// Server and client binaries must register events in the same order.
+    /// Server and client binaries must register events in the same order, except for events with a
+    /// [`SimplenetEvent::EVENT_NAME`].
     ///
     /// If you only want to send acks for this request, then you may use `()` for the response type.
     fn register_simplenet_request_response<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>(
         &mut self,
     ) -> &mut Self;
+
+    /// Registers a request-response event where the request and response are serialized with codec `C` instead
+    /// of the channel's codec.
+    ///
+    /// An event type can only be registered with one codec.
+    fn register_simplenet_request_response_with_codec<
+        E: EventPack,
+        Req: SimplenetEvent,
+        Resp: SimplenetEvent,
+        C: EventCodec,
+    >(
+        &mut self,
+    ) -> &mut Self;
+
+    /// Registers a client request that the server responds to with a stream of chunks.
+    ///
+    /// Server and client binaries must register events in the same order, except for events with a
+    /// [`SimplenetEvent::EVENT_NAME`].
+    ///
+    /// Requests are sent with `EventClient::request` and drained with `ServerRequestSource<E, Req, Chunk>`.
+    /// A chunk type can only be used by one stream.
+    fn register_simplenet_request_stream<E: EventPack, Req: SimplenetEvent, Chunk: SimplenetEvent>(
+        &mut self,
+    ) -> &mut Self;
+
+    /// Registers a stream request where the request and chunks are serialized with codec `C` instead of the
+    /// channel's codec.
+    ///
+    /// An event type can only be registered with one codec.
+    fn register_simplenet_request_stream_with_codec<
+        E: EventPack,
+        Req: SimplenetEvent,
+        Chunk: SimplenetEvent,
+        C: EventCodec,
+    >(
+        &mut self,
+    ) -> &mut Self;
+
+    /// Registers a server-request event with a client response.
+    ///
+    /// Server and client binaries must register events in the same order, except for events with a
+    /// [`SimplenetEvent::EVENT_NAME`].
+    ///
+    /// If you only want to send acks for this request, then you may use `()` for the response type.
+    fn register_simplenet_server_request_response<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>(
+        &mut self,
+    ) -> &mut Self;
+
+    /// Registers a server-request event where the request and response are serialized with codec `C` instead of
+    /// the channel's codec.
+    ///
+    /// An event type can only be registered with one codec.
+    fn register_simplenet_server_request_response_with_codec<
+        E: EventPack,
+        Req: SimplenetEvent,
+        Resp: SimplenetEvent,
+        C: EventCodec,
+    >(
+        &mut self,
+    ) -> &mut Self;
+
+    /// Accesses the event schema for `E`.
+    ///
+    /// Event ids are assigned identically in all feature configurations, so client-only and server-only binaries
+    /// with the same registration order will produce the same schema.
+    ///
+    /// Returns `None` if no events have been registered for `E`.
+    fn simplenet_event_schema<E: EventPack>(&self) -> Option<&EventSchema>;
 }
 
 impl SimplenetEventAppExt for App
 {
     fn register_simplenet_client_message<E: EventPack, T: SimplenetEvent>(&mut self) -> &mut Self
+    {
+        self.register_simplenet_client_message_with_codec::<E, T, ChannelCodec<E>>()
+    }
+
+    fn register_simplenet_client_message_with_codec<E: EventPack, T: SimplenetEvent, C: EventCodec>(
+        &mut self,
+    ) -> &mut Self
     {
         // setup
         setup_simplenet_event_framwork::<E>(self);
 
+        // register type
+        // - this is done in all feature configurations so event ids match between client-only and server-only
+        //   binaries
+        #[cfg_attr(not(feature = "client"), allow(unused_variables))]
+        let message_event_id = self
+            .world_mut()
+            .resource_mut::<EventRegistry<E>>()
+            .register_message::<T, C>();
+
         #[cfg(feature = "client")]
         {
-            // register type
-            let message_event_id = self
-                .world_mut()
-                .resource_mut::<EventRegistry<E>>()
-                .register_message::<T>();
-
             // register event
             self.world_mut()
                 .resource_mut::<EventQueueConnectorClient<E>>()
-                .register_message::<T>(message_event_id);
+                .register_message::<T, C>(message_event_id);
             self.init_resource::<ClientMessageQueue<E, T>>();
         }
 
@@ -148,22 +312,31 @@ impl SimplenetEventAppExt for App
     }
 
     fn register_simplenet_server_message<E: EventPack, T: SimplenetEvent>(&mut self) -> &mut Self
+    {
+        self.register_simplenet_server_message_with_codec::<E, T, ChannelCodec<E>>()
+    }
+
+    fn register_simplenet_server_message_with_codec<E: EventPack, T: SimplenetEvent, C: EventCodec>(
+        &mut self,
+    ) -> &mut Self
     {
         // setup
         setup_simplenet_event_framwork::<E>(self);
 
+        // register type
+        // - see `register_simplenet_client_message_with_codec`
+        #[cfg_attr(not(feature = "server"), allow(unused_variables))]
+        let message_event_id = self
+            .world_mut()
+            .resource_mut::<EventRegistry<E>>()
+            .register_message::<T, C>();
+
         #[cfg(feature = "server")]
         {
-            // register type
-            let message_event_id = self
-                .world_mut()
-                .resource_mut::<EventRegistry<E>>()
-                .register_message::<T>();
-
             // register event
             self.world_mut()
                 .resource_mut::<EventQueueConnectorServer<E>>()
-                .register_message::<T>(message_event_id);
+                .register_message::<T, C>(message_event_id);
             self.init_resource::<ServerMessageQueue<E, T>>();
         }
 
@@ -173,15 +346,28 @@ impl SimplenetEventAppExt for App
     fn register_simplenet_request_response<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>(
         &mut self,
     ) -> &mut Self
+    {
+        self.register_simplenet_request_response_with_codec::<E, Req, Resp, ChannelCodec<E>>()
+    }
+
+    fn register_simplenet_request_response_with_codec<
+        E: EventPack,
+        Req: SimplenetEvent,
+        Resp: SimplenetEvent,
+        C: EventCodec,
+    >(
+        &mut self,
+    ) -> &mut Self
     {
         // setup
         setup_simplenet_event_framwork::<E>(self);
 
         // register type
+        #[cfg_attr(not(any(feature = "client", feature = "server")), allow(unused_variables))]
         let (request_event_id, response_event_id) = self
             .world_mut()
             .resource_mut::<EventRegistry<E>>()
-            .register_request_response::<Req, Resp>();
+            .register_request_response::<Req, Resp, C>();
 
         // register event
         // - requests are read on the server
@@ -190,7 +376,7 @@ impl SimplenetEventAppExt for App
         {
             self.world_mut()
                 .resource_mut::<EventQueueConnectorServer<E>>()
-                .register_request::<Req, Resp>(request_event_id, response_event_id);
+                .register_request::<Req, Resp, C>(request_event_id, response_event_id);
             self.init_resource::<ServerRequestQueue<E, Req, Resp>>();
         }
 
@@ -198,12 +384,115 @@ impl SimplenetEventAppExt for App
         {
             self.world_mut()
                 .resource_mut::<EventQueueConnectorClient<E>>()
-                .register_response::<Req, Resp>(request_event_id, response_event_id);
+                .register_response::<Req, Resp, C>(request_event_id, response_event_id);
             self.init_resource::<ClientResponseQueue<E, Req, Resp>>();
         }
 
         self
     }
+
+    fn register_simplenet_request_stream<E: EventPack, Req: SimplenetEvent, Chunk: SimplenetEvent>(
+        &mut self,
+    ) -> &mut Self
+    {
+        self.register_simplenet_request_stream_with_codec::<E, Req, Chunk, ChannelCodec<E>>()
+    }
+
+    fn register_simplenet_request_stream_with_codec<
+        E: EventPack,
+        Req: SimplenetEvent,
+        Chunk: SimplenetEvent,
+        C: EventCodec,
+    >(
+        &mut self,
+    ) -> &mut Self
+    {
+        // setup
+        setup_simplenet_event_framwork::<E>(self);
+
+        // register type
+        #[cfg_attr(not(any(feature = "client", feature = "server")), allow(unused_variables))]
+        let (request_event_id, chunk_event_id) = self
+            .world_mut()
+            .resource_mut::<EventRegistry<E>>()
+            .register_request_stream::<Req, Chunk, C>();
+
+        // register event
+        // - requests are read on the server
+        // - chunks are read on the client
+        #[cfg(feature = "server")]
+        {
+            self.world_mut()
+                .resource_mut::<EventQueueConnectorServer<E>>()
+                .register_request::<Req, Chunk, C>(request_event_id, chunk_event_id);
+            self.init_resource::<ServerRequestQueue<E, Req, Chunk>>();
+        }
+
+        #[cfg(feature = "client")]
+        {
+            self.world_mut()
+                .resource_mut::<EventQueueConnectorClient<E>>()
+                .register_stream::<Req, Chunk, C>(request_event_id, chunk_event_id);
+            self.init_resource::<ClientStreamQueue<E, Req, Chunk>>();
+        }
+
+        self
+    }
+
+    fn register_simplenet_server_request_response<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>(
+        &mut self,
+    ) -> &mut Self
+    {
+        self.register_simplenet_server_request_response_with_codec::<E, Req, Resp, ChannelCodec<E>>()
+    }
+
+    fn register_simplenet_server_request_response_with_codec<
+        E: EventPack,
+        Req: SimplenetEvent,
+        Resp: SimplenetEvent,
+        C: EventCodec,
+    >(
+        &mut self,
+    ) -> &mut Self
+    {
+        // setup
+        setup_simplenet_event_framwork::<E>(self);
+
+        // register type
+        #[cfg_attr(not(feature = "server"), allow(unused_variables))]
+        let (request_event_id, response_event_id) = self
+            .world_mut()
+            .resource_mut::<EventRegistry<E>>()
+            .register_server_request_response::<Req, Resp, C>();
+
+        // register event
+        // - requests are read on the client
+        // - responses are read on the server
+        #[cfg(feature = "server")]
+        {
+            self.world_mut()
+                .resource_mut::<EventQueueConnectorServer<E>>()
+                .register_client_response::<Req, Resp, C>(request_event_id, response_event_id);
+            self.init_resource::<ServerResponseQueue<E, Req, Resp>>();
+        }
+
+        #[cfg(feature = "client")]
+        {
+            self.world_mut()
+                .resource_mut::<EventQueueConnectorClient<E>>()
+                .register_server_request::<Req, Resp, C>(request_event_id);
+            self.init_resource::<ClientRequestQueue<E, Req, Resp>>();
+        }
+
+        self
+    }
+
+    fn simplenet_event_schema<E: EventPack>(&self) -> Option<&EventSchema>
+    {
+        self.world()
+            .get_resource::<EventRegistry<E>>()
+            .map(|registry| registry.schema())
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
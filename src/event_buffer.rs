@@ -0,0 +1,112 @@
+use bevy_ecs::event::{EventRegistry as BevyEventRegistry, ShouldUpdateEvents};
+use bevy_ecs::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Checks if event buffers should be swapped this tick.
+///
+/// If refreshed in `First`, we follow `bevy_ecs::event::Events`: when `bevy_time` is driving fixed-update
+/// schedules, buffers are only swapped after a fixed-update cycle has run so systems in `FixedUpdate` don't miss
+/// events. Bevy resets its signal in `First`, so refreshes in other schedules always swap.
+pub(crate) fn should_swap_buffers(world: &World, refresh_in_first: bool) -> bool
+{
+    if !refresh_in_first {
+        return true;
+    }
+
+    match world.get_resource::<BevyEventRegistry>() {
+        Some(registry) => registry.should_update != ShouldUpdateEvents::Waiting,
+        None => true,
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Double-buffered event storage with support for per-system read cursors.
+///
+/// Events received since the last [`Self::update`] can be iterated with [`Self::iter`]. Cursors can read events
+/// for two buffer swaps, the same as `bevy_ecs::event::Events`.
+pub(crate) struct EventBuffer<T>
+{
+    /// Older events.
+    events_a: Vec<Option<T>>,
+    /// Newer events.
+    events_b: Vec<Option<T>>,
+    /// Event count of the first event in `events_a`.
+    start_count: usize,
+    /// Event count of the first event received since the last update.
+    tick_start: usize,
+}
+
+impl<T> EventBuffer<T>
+{
+    fn event_count(&self) -> usize
+    {
+        self.start_count + self.events_a.len() + self.events_b.len()
+    }
+
+    fn iter_from(&self, count: usize) -> impl Iterator<Item = &T> + '_
+    {
+        let skip = count.saturating_sub(self.start_count);
+        self.events_a
+            .iter()
+            .chain(self.events_b.iter())
+            .skip(skip)
+            .filter_map(|e| e.as_ref())
+    }
+
+    /// Starts a new tick, dropping the older buffer if `swap` is true.
+    pub(crate) fn update(&mut self, swap: bool)
+    {
+        if swap {
+            self.start_count += self.events_a.len();
+            std::mem::swap(&mut self.events_a, &mut self.events_b);
+            self.events_b.clear();
+        }
+        self.tick_start = self.event_count();
+    }
+
+    /// Removes events that match the filter so they won't be read.
+    pub(crate) fn discard(&mut self, mut filter: impl FnMut(&T) -> bool)
+    {
+        self.events_a
+            .iter_mut()
+            .chain(self.events_b.iter_mut())
+            .filter(|e| e.as_ref().is_some_and(&mut filter))
+            .for_each(|e| *e = None);
+    }
+
+    pub(crate) fn send(&mut self, event: T)
+    {
+        self.events_b.push(Some(event));
+    }
+
+    /// Iterates events received since the last update.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &T> + '_
+    {
+        self.iter_from(self.tick_start)
+    }
+
+    /// Iterates buffered events that the cursor hasn't read yet, then moves the cursor to the end.
+    pub(crate) fn read(&self, cursor: &mut usize) -> impl Iterator<Item = &T> + '_
+    {
+        let start = *cursor;
+        *cursor = self.event_count();
+        self.iter_from(start)
+    }
+}
+
+impl<T> Default for EventBuffer<T>
+{
+    fn default() -> Self
+    {
+        Self {
+            events_a: Vec::default(),
+            events_b: Vec::default(),
+            start_count: 0,
+            tick_start: 0,
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -1,6 +1,9 @@
-use std::any::TypeId;
+use std::any::{Any, TypeId};
+use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
+#[cfg(any(feature = "client", feature = "server"))]
+use std::time::Duration;
 
 use bevy_ecs::prelude::*;
 
@@ -8,6 +11,29 @@ use crate::*;
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Event ids with this bit set are derived from [`SimplenetEvent::EVENT_NAME`] or [`SimplenetEvent::EVENT_ID`].
+/// Other event ids are assigned in registration order.
+const NAMED_ID_FLAG: u16 = 0x8000;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Maps an event name to a stable event id.
+///
+/// The hash is folded into the 15 bits below [`NAMED_ID_FLAG`], so by the birthday bound collisions are likely
+/// once a few hundred names are registered. Collisions are detected at registration.
+fn named_event_id(name: &str) -> u16
+{
+    let hash = fnv1a(FNV1A_OFFSET, name.as_bytes());
+    let folded = (hash ^ (hash >> 16) ^ (hash >> 32) ^ (hash >> 48)) as u16;
+    NAMED_ID_FLAG | (folded & !NAMED_ID_FLAG)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+type EncodeFn<T> = fn(&T) -> Result<Vec<u8>, CodecError>;
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Used to register simplenet event types that can be sent over the network.
 ///
 /// We parameterize on `E` so the registry does not cause interference between multiple clients/servers in the same
@@ -17,19 +43,123 @@ use crate::*;
 pub(crate) struct EventRegistry<E: EventPack>
 {
     id_counter: u16,
+    /// [ named event id : (type id, event name) ]
+    named_ids: HashMap<u16, (TypeId, &'static str)>,
     message_map: HashMap<TypeId, u16>,
     message_ids: HashSet<u16>,
     request_map: HashMap<TypeId, u16>,
     response_map: HashMap<TypeId, u16>,
     request_response_map: HashMap<TypeId, TypeId>,
     request_response_ids: HashMap<u16, u16>,
+    server_request_map: HashMap<TypeId, u16>,
+    /// [ server request event id : client response event id ]
+    server_request_response_ids: HashMap<u16, u16>,
+    /// [ client response event id : response type id ]
+    client_response_types: HashMap<u16, TypeId>,
+    stream_chunk_map: HashMap<TypeId, u16>,
+    stream_chunk_ids: HashSet<u16>,
+    /// [ stream request event id : stream chunk event id ]
+    stream_ids: HashMap<u16, u16>,
+    /// [ request event id : default timeout ]
+    #[cfg(feature = "client")]
+    request_timeouts: HashMap<u16, Duration>,
+    /// [ server request event id : default timeout ]
+    #[cfg(feature = "server")]
+    server_request_timeouts: HashMap<u16, Duration>,
+    /// [ event type id : (codec type id, type-erased `EncodeFn<T>`) ]
+    encoders: HashMap<TypeId, (TypeId, Box<dyn Any + Send + Sync>)>,
+    schema: EventSchema,
     phantom: PhantomData<E>,
 }
 
 impl<E: EventPack> EventRegistry<E>
 {
-    pub(crate) fn register_message<T: SimplenetEvent>(&mut self) -> u16
+    fn next_sequential_id(&mut self) -> u16
+    {
+        self.id_counter += 1;
+        if self.id_counter >= CANCEL_EVENT_ID {
+            panic!("too many simplenet events registered");
+        }
+        self.id_counter
+    }
+
+    /// Gets the stable id for a named event type.
+    ///
+    /// Panics if a different event type has the same id.
+    fn named_id<T: SimplenetEvent>(&mut self) -> Option<u16>
+    {
+        let (id, name) = match (T::EVENT_ID, T::EVENT_NAME) {
+            (Some(id), _) => {
+                if id & NAMED_ID_FLAG != 0 {
+                    panic!(
+                        "simplenet event {} has explicit event id {id}; explicit event ids must be less than \
+                        {NAMED_ID_FLAG}",
+                        std::any::type_name::<T>()
+                    );
+                }
+                (NAMED_ID_FLAG | id, std::any::type_name::<T>())
+            }
+            (None, Some(name)) => (named_event_id(name), name),
+            (None, None) => return None,
+        };
+        let type_id = std::any::TypeId::of::<T>();
+
+        match self.named_ids.entry(id) {
+            Entry::Occupied(entry) => {
+                let (other_type_id, other_name) = *entry.get();
+                if other_type_id != type_id {
+                    panic!(
+                        "simplenet event {} with name {name:?} has the same event id ({id}) as another event with \
+                        name {other_name:?}; event names must be unique (use `#[simplenet(id = 1234)]` to set an \
+                        explicit id if the names differ)",
+                        std::any::type_name::<T>()
+                    );
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert((type_id, name));
+            }
+        }
+
+        Some(id)
+    }
+
+    /// Gets a new event id for an event type.
+    fn assign_id<T: SimplenetEvent>(&mut self) -> u16
+    {
+        match self.named_id::<T>() {
+            Some(id) => id,
+            None => self.next_sequential_id(),
+        }
+    }
+
+    /// Records the codec for an event type.
+    ///
+    /// Panics if the event type was already registered with a different codec.
+    fn register_codec<T: SimplenetEvent, C: EventCodec>(&mut self)
     {
+        match self.encoders.entry(std::any::TypeId::of::<T>()) {
+            Entry::Occupied(entry) => {
+                if entry.get().0 != std::any::TypeId::of::<C>() {
+                    panic!(
+                        "simplenet event {} was registered with codec {} and another codec; an event type can \
+                        only have one codec",
+                        std::any::type_name::<T>(),
+                        std::any::type_name::<C>()
+                    );
+                }
+            }
+            Entry::Vacant(entry) => {
+                let encode: EncodeFn<T> = C::encode::<T>;
+                entry.insert((std::any::TypeId::of::<C>(), Box::new(encode)));
+            }
+        }
+    }
+
+    pub(crate) fn register_message<T: SimplenetEvent, C: EventCodec>(&mut self) -> u16
+    {
+        self.register_codec::<T, C>();
+
         // allow re-entry in case of client/server having same message type
         let type_id = std::any::TypeId::of::<T>();
         if let Some(id) = self.message_map.get(&type_id) {
@@ -37,23 +167,27 @@ impl<E: EventPack> EventRegistry<E>
         }
 
         // make new entry
-        self.id_counter += 1;
-        let id = self.id_counter;
+        let id = self.assign_id::<T>();
 
         if self.message_map.insert(type_id, id).is_some() {
             panic!("message map has this key already");
         }
         self.message_ids.insert(id);
+        self.schema
+            .push(id, std::any::type_name::<T>(), EventKind::Message);
 
         id
     }
 
-    pub(crate) fn register_request_response<Req: SimplenetEvent, Resp: SimplenetEvent>(&mut self) -> (u16, u16)
+    pub(crate) fn register_request_response<Req: SimplenetEvent, Resp: SimplenetEvent, C: EventCodec>(
+        &mut self,
+    ) -> (u16, u16)
     {
-        self.id_counter += 1;
-        let req_id = self.id_counter;
-        self.id_counter += 1;
-        let resp_id = self.id_counter;
+        self.register_codec::<Req, C>();
+        self.register_codec::<Resp, C>();
+
+        let req_id = self.assign_id::<Req>();
+        let resp_id = self.assign_id::<Resp>();
 
         let req_type_id = std::any::TypeId::of::<Req>();
         let resp_type_id = std::any::TypeId::of::<Resp>();
@@ -72,48 +206,230 @@ impl<E: EventPack> EventRegistry<E>
         if self.request_response_ids.insert(req_id, resp_id).is_some() {
             panic!("request_response_ids has this key already");
         }
+        self.schema
+            .push(req_id, std::any::type_name::<Req>(), EventKind::Request);
+        self.schema
+            .push(resp_id, std::any::type_name::<Resp>(), EventKind::Response);
 
         (req_id, resp_id)
     }
 
+    pub(crate) fn register_server_request_response<Req: SimplenetEvent, Resp: SimplenetEvent, C: EventCodec>(
+        &mut self,
+    ) -> (u16, u16)
+    {
+        self.register_codec::<Req, C>();
+        self.register_codec::<Resp, C>();
+
+        let req_id = self.assign_id::<Req>();
+        let resp_id = self.assign_id::<Resp>();
+
+        if self
+            .server_request_map
+            .insert(std::any::TypeId::of::<Req>(), req_id)
+            .is_some()
+        {
+            panic!("server_request_map has this key already");
+        }
+        if self
+            .server_request_response_ids
+            .insert(req_id, resp_id)
+            .is_some()
+        {
+            panic!("server_request_response_ids has this key already");
+        }
+        self.client_response_types
+            .insert(resp_id, std::any::TypeId::of::<Resp>());
+        self.schema
+            .push(req_id, std::any::type_name::<Req>(), EventKind::ServerRequest);
+        self.schema
+            .push(resp_id, std::any::type_name::<Resp>(), EventKind::ClientResponse);
+
+        (req_id, resp_id)
+    }
+
+    pub(crate) fn register_request_stream<Req: SimplenetEvent, Chunk: SimplenetEvent, C: EventCodec>(
+        &mut self,
+    ) -> (u16, u16)
+    {
+        self.register_codec::<Req, C>();
+        self.register_codec::<Chunk, C>();
+
+        let req_id = self.assign_id::<Req>();
+        let chunk_id = self.assign_id::<Chunk>();
+
+        if self
+            .request_map
+            .insert(std::any::TypeId::of::<Req>(), req_id)
+            .is_some()
+        {
+            panic!("request_map has this key already");
+        }
+        if self
+            .stream_chunk_map
+            .insert(std::any::TypeId::of::<Chunk>(), chunk_id)
+            .is_some()
+        {
+            panic!(
+                "stream chunk type {} was already registered; a chunk type can only be used by one stream",
+                std::any::type_name::<Chunk>()
+            );
+        }
+        if self.request_response_ids.insert(req_id, chunk_id).is_some() {
+            panic!("request_response_ids has this key already");
+        }
+        self.stream_ids.insert(req_id, chunk_id);
+        self.stream_chunk_ids.insert(chunk_id);
+        self.schema
+            .push(req_id, std::any::type_name::<Req>(), EventKind::Request);
+        self.schema
+            .push(chunk_id, std::any::type_name::<Chunk>(), EventKind::StreamChunk);
+
+        (req_id, chunk_id)
+    }
+
+    /// Serializes an event with its registered codec.
+    ///
+    /// Falls back to the channel codec if the event type is not registered.
+    pub(crate) fn encode<T: SimplenetEvent>(&self, event: &T) -> Result<Vec<u8>, CodecError>
+    {
+        let Some((_, encode)) = self.encoders.get(&std::any::TypeId::of::<T>()) else {
+            return E::encode(event);
+        };
+        let Some(encode) = encode.downcast_ref::<EncodeFn<T>>() else {
+            return Err(CodecError::new("event encoder has the wrong type"));
+        };
+
+        encode(event)
+    }
+
     pub(crate) fn get_message_id<T: SimplenetEvent>(&self) -> Option<u16>
     {
-        self.message_map
-            .get(&std::any::TypeId::of::<T>())
-            .map(|i| *i)
+        self.message_map.get(&std::any::TypeId::of::<T>()).copied()
     }
 
+    #[cfg(feature = "server")]
     pub(crate) fn has_message_id(&self, id: u16) -> bool
     {
         self.message_ids.contains(&id)
     }
 
+    #[cfg(feature = "client")]
     pub(crate) fn get_request_id<Req: SimplenetEvent>(&self) -> Option<u16>
     {
         self.request_map
             .get(&std::any::TypeId::of::<Req>())
-            .map(|i| *i)
+            .copied()
     }
 
+    #[cfg(feature = "server")]
     pub(crate) fn get_response_id<Resp: SimplenetEvent>(&self) -> Option<u16>
     {
         self.response_map
             .get(&std::any::TypeId::of::<Resp>())
-            .map(|i| *i)
+            .copied()
     }
 
+    /// Gets the response event id for a request type, or the chunk event id if the request is a stream request.
+    #[cfg(feature = "client")]
     pub(crate) fn get_response_id_from_request<Req: SimplenetEvent>(&self) -> Option<u16>
     {
         self.request_response_map
             .get(&std::any::TypeId::of::<Req>())
-            .map(|t| self.response_map.get(t).map(|i| *i))
-            .flatten()
+            .and_then(|t| self.response_map.get(t).copied())
+            .or_else(|| {
+                self.get_request_id::<Req>()
+                    .and_then(|id| self.stream_ids.get(&id).copied())
+            })
     }
 
+    #[cfg(feature = "server")]
+    pub(crate) fn get_stream_chunk_id<Chunk: SimplenetEvent>(&self) -> Option<u16>
+    {
+        self.stream_chunk_map
+            .get(&std::any::TypeId::of::<Chunk>())
+            .copied()
+    }
+
+    /// Sets the default timeout for requests of a request type.
+    #[cfg(feature = "client")]
+    pub(crate) fn set_request_timeout(&mut self, request_event_id: u16, timeout: Duration)
+    {
+        self.request_timeouts.insert(request_event_id, timeout);
+    }
+
+    #[cfg(feature = "client")]
+    pub(crate) fn get_request_timeout(&self, request_event_id: u16) -> Option<Duration>
+    {
+        self.request_timeouts.get(&request_event_id).copied()
+    }
+
+    /// Sets the default timeout for server requests of a request type.
+    #[cfg(feature = "server")]
+    pub(crate) fn set_server_request_timeout(&mut self, request_event_id: u16, timeout: Duration)
+    {
+        self.server_request_timeouts
+            .insert(request_event_id, timeout);
+    }
+
+    #[cfg(feature = "server")]
+    pub(crate) fn get_server_request_timeout(&self, request_event_id: u16) -> Option<Duration>
+    {
+        self.server_request_timeouts.get(&request_event_id).copied()
+    }
+
+    #[cfg(feature = "client")]
+    pub(crate) fn is_stream_chunk_id(&self, id: u16) -> bool
+    {
+        self.stream_chunk_ids.contains(&id)
+    }
+
+    #[cfg(feature = "server")]
     pub(crate) fn get_response_id_from_request_id(&self, request_event_id: u16) -> Option<u16>
     {
         self.request_response_ids.get(&request_event_id).copied()
     }
+
+    /// Gets the request and response event ids for a server request type.
+    #[cfg(feature = "server")]
+    pub(crate) fn get_server_request_ids<Req: SimplenetEvent>(&self) -> Option<(u16, u16)>
+    {
+        let request_event_id = self
+            .server_request_map
+            .get(&std::any::TypeId::of::<Req>())
+            .copied()?;
+        let response_event_id = self
+            .server_request_response_ids
+            .get(&request_event_id)
+            .copied()?;
+        Some((request_event_id, response_event_id))
+    }
+
+    #[cfg(feature = "server")]
+    pub(crate) fn has_client_response_id(&self, id: u16) -> bool
+    {
+        self.client_response_types.contains_key(&id)
+    }
+
+    #[cfg(feature = "client")]
+    pub(crate) fn get_client_response_id_from_server_request_id(&self, request_event_id: u16) -> Option<u16>
+    {
+        self.server_request_response_ids
+            .get(&request_event_id)
+            .copied()
+    }
+
+    /// Checks if `Resp` is the response type registered for a client response event id.
+    #[cfg(feature = "client")]
+    pub(crate) fn is_client_response_type<Resp: SimplenetEvent>(&self, response_event_id: u16) -> bool
+    {
+        self.client_response_types.get(&response_event_id) == Some(&std::any::TypeId::of::<Resp>())
+    }
+
+    pub(crate) fn schema(&self) -> &EventSchema
+    {
+        &self.schema
+    }
 }
 
 impl<E: EventPack> Default for EventRegistry<E>
@@ -122,13 +438,26 @@ impl<E: EventPack> Default for EventRegistry<E>
     {
         Self {
             id_counter: 0u16,
+            named_ids: HashMap::default(),
             message_map: HashMap::default(),
             message_ids: HashSet::default(),
             request_map: HashMap::default(),
             response_map: HashMap::default(),
             request_response_map: HashMap::default(),
             request_response_ids: HashMap::default(),
-            phantom: PhantomData::default(),
+            server_request_map: HashMap::default(),
+            server_request_response_ids: HashMap::default(),
+            client_response_types: HashMap::default(),
+            stream_chunk_map: HashMap::default(),
+            stream_chunk_ids: HashSet::default(),
+            stream_ids: HashMap::default(),
+            #[cfg(feature = "client")]
+            request_timeouts: HashMap::default(),
+            #[cfg(feature = "server")]
+            server_request_timeouts: HashMap::default(),
+            encoders: HashMap::default(),
+            schema: EventSchema::default(),
+            phantom: PhantomData,
         }
     }
 }
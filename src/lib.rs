@@ -7,8 +7,15 @@
 use crate as bevy_simplenet_events;
 
 //module tree
+mod codec;
 mod core;
+#[cfg(any(feature = "client", feature = "server"))]
+mod event_buffer;
 mod event_registry;
+#[cfg(any(feature = "client", feature = "server"))]
+mod request_frame;
+mod schema;
+mod send_error;
 
 #[cfg(feature = "client")]
 #[cfg_attr(docsrs, doc(cfg(feature = "client")))]
@@ -23,7 +30,14 @@ pub use bevy_simplenet_events_derive::*;
 
 #[cfg(feature = "client")]
 pub use crate::client::*;
+pub use crate::codec::*;
 pub use crate::core::*;
+#[cfg(any(feature = "client", feature = "server"))]
+pub(crate) use crate::event_buffer::*;
 pub(crate) use crate::event_registry::*;
+#[cfg(any(feature = "client", feature = "server"))]
+pub(crate) use crate::request_frame::*;
+pub use crate::schema::*;
+pub use crate::send_error::*;
 #[cfg(feature = "server")]
 pub use crate::server::*;
@@ -0,0 +1,120 @@
+//-------------------------------------------------------------------------------------------------------------------
+
+//note: server requests, client responses, stream chunks, and request cancellations are sent as `bevy_simplenet`
+//      messages, so their request ids are carried in the event data
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Event id of server responses that fail a stream.
+///
+/// Streams are failed with a response instead of a rejection because rejections aren't ordered with messages, so
+/// they could overtake the stream's chunks. The schema event id is reused since the schema is never a response.
+pub(crate) const STREAM_ERROR_EVENT_ID: u16 = crate::SCHEMA_EVENT_ID;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// How a client resolved a server request.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum ClientResponseKind
+{
+    Response,
+    Ack,
+    Reject,
+}
+
+impl ClientResponseKind
+{
+    #[cfg(feature = "client")]
+    fn to_byte(self) -> u8
+    {
+        match self {
+            Self::Response => 0u8,
+            Self::Ack => 1u8,
+            Self::Reject => 2u8,
+        }
+    }
+
+    #[cfg(feature = "server")]
+    fn from_byte(byte: u8) -> Option<Self>
+    {
+        match byte {
+            0u8 => Some(Self::Response),
+            1u8 => Some(Self::Ack),
+            2u8 => Some(Self::Reject),
+            _ => None,
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Prefixes a serialized server request or stream chunk with its request id.
+#[cfg(feature = "server")]
+pub(crate) fn encode_request_id_frame(request_id: u64, payload: &[u8]) -> Vec<u8>
+{
+    let mut data = Vec::with_capacity(8 + payload.len());
+    data.extend_from_slice(&request_id.to_le_bytes());
+    data.extend_from_slice(payload);
+    data
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Splits a server request or stream chunk frame into its request id and serialized event.
+///
+/// Returns `None` if the frame is truncated.
+#[cfg(feature = "client")]
+pub(crate) fn decode_request_id_frame(data: &[u8]) -> Option<(u64, &[u8])>
+{
+    let (id, payload) = data.split_first_chunk::<8>()?;
+    Some((u64::from_le_bytes(*id), payload))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Makes a cancellation notice for a client request.
+#[cfg(feature = "client")]
+pub(crate) fn encode_cancel_frame(request_id: u64) -> Vec<u8>
+{
+    request_id.to_le_bytes().to_vec()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Reads the request id from a cancellation notice.
+///
+/// Returns `None` if the frame is malformed.
+#[cfg(feature = "server")]
+pub(crate) fn decode_cancel_frame(data: &[u8]) -> Option<u64>
+{
+    let id: [u8; 8] = data.try_into().ok()?;
+    Some(u64::from_le_bytes(id))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Prefixes a serialized client response with its request id and response kind.
+#[cfg(feature = "client")]
+pub(crate) fn encode_client_response_frame(request_id: u64, kind: ClientResponseKind, payload: &[u8]) -> Vec<u8>
+{
+    let mut data = Vec::with_capacity(9 + payload.len());
+    data.extend_from_slice(&request_id.to_le_bytes());
+    data.push(kind.to_byte());
+    data.extend_from_slice(payload);
+    data
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Splits a client response frame into its request id, response kind, and serialized response.
+///
+/// Returns `None` if the frame is truncated or the response kind is unknown.
+#[cfg(feature = "server")]
+pub(crate) fn decode_client_response_frame(data: &[u8]) -> Option<(u64, ClientResponseKind, &[u8])>
+{
+    let (id, rest) = data.split_first_chunk::<8>()?;
+    let (kind, payload) = rest.split_first()?;
+    Some((u64::from_le_bytes(*id), ClientResponseKind::from_byte(*kind)?, payload))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Reserved event id used to exchange [`EventSchema`]s when a connection is established.
+///
+/// Registered events are assigned ids starting at `1`, so this id never collides with user events.
+pub(crate) const SCHEMA_EVENT_ID: u16 = 0;
+
+/// Reserved event id used by clients to cancel requests.
+///
+/// This is the highest sequential event id, which is never assigned to user events. Named event ids always have
+/// the high bit set, so they can't collide with it either.
+pub(crate) const CANCEL_EVENT_ID: u16 = 0x7FFF;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Initial state for [`fnv1a`].
+pub(crate) const FNV1A_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Stable 64-bit FNV-1a hash.
+///
+/// We don't use `std::hash::DefaultHasher` because its output is not guaranteed to be stable across Rust releases,
+/// and server/client binaries may be built with different toolchains.
+pub(crate) fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64
+{
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// The role of a registered event.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum EventKind
+{
+    /// A one-shot message.
+    Message,
+    /// A client request.
+    Request,
+    /// A server response to a client request.
+    Response,
+    /// A server request.
+    ServerRequest,
+    /// A client response to a server request.
+    ClientResponse,
+    /// A server stream chunk for a client request.
+    StreamChunk,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// One registered event in an [`EventSchema`].
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Hash)]
+pub struct SchemaEntry
+{
+    /// The event's wire id.
+    pub id: u16,
+    /// The event's type name, from [`std::any::type_name`].
+    pub type_name: String,
+    /// The event's role.
+    pub kind: EventKind,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// The set of events registered for an [`EventPack`](crate::EventPack).
+///
+/// Servers and clients exchange schemas when a connection is established. See [`SchemaMismatch`].
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct EventSchema
+{
+    entries: Vec<SchemaEntry>,
+}
+
+impl EventSchema
+{
+    /// Adds an entry to the schema.
+    ///
+    /// Entries are sorted by id so the schema doesn't depend on registration order when event ids are derived
+    /// from event names. Duplicate entries are ignored.
+    pub(crate) fn push(&mut self, id: u16, type_name: &str, kind: EventKind)
+    {
+        let index = match self
+            .entries
+            .binary_search_by(|e| (e.id, e.kind, e.type_name.as_str()).cmp(&(id, kind, type_name)))
+        {
+            Ok(_) => return,
+            Err(index) => index,
+        };
+        self.entries
+            .insert(index, SchemaEntry { id, type_name: type_name.into(), kind });
+    }
+
+    /// Iterates the schema's entries in event id order.
+    pub fn iter(&self) -> impl Iterator<Item = &SchemaEntry> + '_
+    {
+        self.entries.iter()
+    }
+
+    /// Computes a fingerprint from the ids, type names, and kinds of all entries.
+    pub fn fingerprint(&self) -> u64
+    {
+        let mut hash = FNV1A_OFFSET;
+        for entry in self.entries.iter() {
+            hash = fnv1a(hash, &entry.id.to_le_bytes());
+            hash = fnv1a(hash, &(entry.type_name.len() as u64).to_le_bytes());
+            hash = fnv1a(hash, entry.type_name.as_bytes());
+            hash = fnv1a(hash, &[entry.kind as u8]);
+        }
+        hash
+    }
+
+    /// Compares this schema with a schema received from the other side of a connection.
+    ///
+    /// Returns `None` if the schemas match.
+    pub(crate) fn compare(&self, remote: &EventSchema) -> Option<SchemaMismatch>
+    {
+        let local_fingerprint = self.fingerprint();
+        let remote_fingerprint = remote.fingerprint();
+        if local_fingerprint == remote_fingerprint {
+            return None;
+        }
+
+        let len = self.entries.len().max(remote.entries.len());
+        let first_difference = (0..len).find_map(|i| {
+            let local = self.entries.get(i);
+            let remote = remote.entries.get(i);
+            if local == remote {
+                return None;
+            }
+            Some(SchemaDifference { local: local.cloned(), remote: remote.cloned() })
+        });
+
+        Some(SchemaMismatch {
+            local_fingerprint,
+            remote_fingerprint: Some(remote_fingerprint),
+            first_difference,
+        })
+    }
+
+    /// Makes a mismatch report for a schema that could not be decoded.
+    pub(crate) fn undecodable(&self) -> SchemaMismatch
+    {
+        SchemaMismatch {
+            local_fingerprint: self.fingerprint(),
+            remote_fingerprint: None,
+            first_difference: None,
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// The first position where two [`EventSchema`]s differ.
+///
+/// An entry is `None` if that side registered fewer events.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SchemaDifference
+{
+    pub local: Option<SchemaEntry>,
+    pub remote: Option<SchemaEntry>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Reports that the other side of a connection registered different events than this side.
+///
+/// Events must be registered identically on the server and client, otherwise messages will be misrouted or
+/// dropped.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SchemaMismatch
+{
+    /// Fingerprint of the local schema.
+    pub local_fingerprint: u64,
+    /// Fingerprint of the remote schema, or `None` if the remote schema could not be decoded.
+    pub remote_fingerprint: Option<u64>,
+    /// The first differing entry, if the remote schema could be decoded.
+    pub first_difference: Option<SchemaDifference>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Controls how servers handle clients with mismatched [`EventSchema`]s.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum SchemaPolicy
+{
+    /// Report mismatches with [`ServerConnectionReader::schema_mismatches`](crate::ServerConnectionReader).
+    #[default]
+    Report,
+    /// Report mismatches and disconnect the client.
+    ///
+    /// A client's `ServerReport::Connected` is held until the client's schema arrives, so the server can't
+    /// exchange events with the client until its schema is verified. Mismatched clients are never reported as
+    /// connected or disconnected.
+    Disconnect,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
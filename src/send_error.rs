@@ -0,0 +1,103 @@
+use std::fmt::Display;
+
+use bevy_simplenet::ClientId;
+
+use crate::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Reason an event could not be sent.
+#[derive(Debug)]
+pub enum SendError
+{
+    /// The most recent connection report for the target has not been read yet.
+    ///
+    /// See the synchronization guarantees in the crate docs.
+    PendingConnect,
+    /// The target client is not connected.
+    ///
+    /// Only returned by the server.
+    UnknownClient,
+    /// The event type is not registered.
+    UnregisteredType,
+    /// The request type has no registered response type.
+    ///
+    /// Only returned by the client.
+    UnregisteredResponse,
+    /// The request being responded to is from an old connection session.
+    StaleRequest,
+    /// The event failed to serialize.
+    Encode(CodecError),
+}
+
+impl Display for SendError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self {
+            Self::PendingConnect => f.write_str("there is an unread pending connect event"),
+            Self::UnknownClient => f.write_str("the client is not connected"),
+            Self::UnregisteredType => f.write_str("the event type is not registered"),
+            Self::UnregisteredResponse => f.write_str("the request type has no registered response type"),
+            Self::StaleRequest => f.write_str("the request is from an old connection session"),
+            Self::Encode(err) => write!(f, "failed serializing event: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for SendError
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)>
+    {
+        match self {
+            Self::Encode(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Reason a message could not be sent to some or all of its target clients.
+///
+/// Only returned by the server.
+#[derive(Debug)]
+pub enum SendManyError
+{
+    /// The message could not be sent to any client.
+    Message(SendError),
+    /// The message could not be sent to these clients. It was sent to all other target clients.
+    Clients(Vec<(ClientId, SendError)>),
+}
+
+impl Display for SendManyError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self {
+            Self::Message(err) => Display::fmt(err, f),
+            Self::Clients(failures) => {
+                write!(f, "failed sending message to {} client(s)", failures.len())?;
+                if let Some((client_id, err)) = failures.first() {
+                    write!(f, " (client {client_id}: {err})")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SendManyError
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)>
+    {
+        match self {
+            Self::Message(err) => Some(err),
+            Self::Clients(failures) => failures
+                .first()
+                .map(|(_, err)| err as &(dyn std::error::Error + 'static)),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -1,6 +1,9 @@
-use bevy_app::{App, First};
+use std::time::Duration;
+
+use bevy_app::{App, First, Last};
 use bevy_ecs::prelude::*;
-use bevy_simplenet::{Server, ServerEvent, ServerReport};
+use bevy_ecs::schedule::ScheduleLabel;
+use bevy_simplenet::{ClientId, Server, ServerEvent, ServerReport};
 
 use crate::*;
 
@@ -10,13 +13,86 @@ use crate::*;
 
 //-------------------------------------------------------------------------------------------------------------------
 
+type InnerServerEvent<C> = ServerEvent<C, InternalEvent, InternalEvent>;
+
 //-------------------------------------------------------------------------------------------------------------------
 
-type InnerServerEvent<C> = ServerEvent<C, InternalEvent, InternalEvent>;
+fn handle_client_schema<E: EventPack>(
+    world: &mut World,
+    server: &mut EventServerCore<E>,
+    queues: &EventQueueConnectorServer<E>,
+    registry: &EventRegistry<E>,
+    client_id: ClientId,
+    data: Vec<u8>,
+)
+{
+    let mismatch = match BincodeCodec::decode::<EventSchema>(&data[..]) {
+        Ok(remote) => registry.schema().compare(&remote),
+        Err(_) => Some(registry.schema().undecodable()),
+    };
+    let Some(mismatch) = mismatch else {
+        // release the client's connect report now that its schema is verified
+        if let Some((counter, report)) = server.verify_client(client_id) {
+            update_client_entity::<E>(world, client_id, &report);
+            queues.send_connection(world, counter, client_id, report);
+        }
+        return;
+    };
+
+    tracing::warn!(client_id, ?mismatch, "client event schema does not match server event schema");
+    queues.send_schema_mismatch(world, client_id, mismatch);
+
+    if server.schema_policy() == SchemaPolicy::Disconnect {
+        server.reject_unverified_client(client_id);
+        server.disconnect_client(client_id, None);
+    }
+}
 
 //-------------------------------------------------------------------------------------------------------------------
 
-fn drain_server<E: EventPack>(world: &mut World)
+fn handle_client_response<E: EventPack>(
+    world: &mut World,
+    server: &mut EventServerCore<E>,
+    queues: &EventQueueConnectorServer<E>,
+    client_id: ClientId,
+    response: InternalEvent,
+)
+{
+    let Some((request_id, kind, payload)) = decode_client_response_frame(&response.data) else {
+        tracing::warn!(client_id, response.id, "ignoring truncated client response");
+        return;
+    };
+
+    // note: requests are only removed if they were sent to this client, so clients can't resolve each other's
+    //       requests
+    let Some((request_event_id, response_event_id)) = server.remove_request(client_id, request_id) else {
+        tracing::debug!(client_id, request_id, "ignoring client response for unknown or timed-out request id");
+        return;
+    };
+
+    let data = if response.id != response_event_id {
+        tracing::warn!(
+            client_id,
+            request_id,
+            response.id,
+            response_event_id,
+            "received malformed client response"
+        );
+        PendingClientResponseData::Malformed
+    } else {
+        match kind {
+            ClientResponseKind::Response => PendingClientResponseData::Response(payload.to_vec()),
+            ClientResponseKind::Ack => PendingClientResponseData::Ack,
+            ClientResponseKind::Reject => PendingClientResponseData::Reject,
+        }
+    };
+
+    queues.send_client_response(world, client_id, request_event_id, response_event_id, request_id, data);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn drain_server<E: EventPack>(world: &mut World, refresh_in_first: bool)
 {
     let mut server = world.remove_resource::<EventServerCore<E>>().unwrap();
     let queues = world
@@ -25,20 +101,82 @@ fn drain_server<E: EventPack>(world: &mut World)
     let registry = world.remove_resource::<EventRegistry<E>>().unwrap();
 
     // clear existing events
-    queues.clear_all(world);
+    let swap_buffers = should_swap_buffers(world, refresh_in_first);
+    queues.clear_all(world, swap_buffers);
 
     // drain events
     while let Some((counter, session_id, event)) = server.next() {
         match event {
             InnerServerEvent::Report(report) => {
                 match &report {
-                    ServerReport::<E::ConnectMsg>::Connected(..) => (),
-                    _ => queues.handle_disconnect(world, session_id),
+                    ServerReport::<E::ConnectMsg>::Connected(..) => {
+                        server.send_schema(&registry, session_id);
+
+                        // hold the report until the client's schema arrives
+                        // - the pending-connect gate stays closed, so nothing can be sent to the client
+                        if server.schema_policy() == SchemaPolicy::Disconnect {
+                            server.hold_connect(session_id, counter, report);
+                            continue;
+                        }
+                    }
+                    _ => {
+                        queues.handle_disconnect(world, session_id);
+                        remove_client_pending_requests::<E>(world, session_id);
+                        world
+                            .resource_mut::<ServerRooms<E>>()
+                            .remove_client(session_id);
+
+                        // resolve requests that the client can no longer respond to
+                        for (request_id, request_event_id, response_event_id) in
+                            server.remove_client_requests(session_id)
+                        {
+                            queues.send_client_response(
+                                world,
+                                session_id,
+                                request_event_id,
+                                response_event_id,
+                                request_id,
+                                PendingClientResponseData::ResponseLost,
+                            );
+                        }
+
+                        // users never saw this client connect
+                        if server.remove_unverified_client(session_id) {
+                            continue;
+                        }
+                    }
                 }
 
+                update_client_entity::<E>(world, session_id, &report);
                 queues.send_connection(world, counter, session_id, report);
             }
             InnerServerEvent::Msg(message) => {
+                if message.id == SCHEMA_EVENT_ID {
+                    handle_client_schema(world, &mut server, &queues, &registry, session_id, message.data);
+                    continue;
+                }
+
+                if server.is_unverified_client(session_id) {
+                    tracing::trace!(session_id, "ignoring message from client with unverified schema");
+                    continue;
+                }
+
+                if message.id == CANCEL_EVENT_ID {
+                    let Some(request_id) = decode_cancel_frame(&message.data) else {
+                        tracing::warn!(session_id, "ignoring malformed request cancellation");
+                        continue;
+                    };
+                    world
+                        .resource_mut::<ServerCancelQueue<E>>()
+                        .send(session_id, request_id);
+                    continue;
+                }
+
+                if registry.has_client_response_id(message.id) {
+                    handle_client_response(world, &mut server, &queues, session_id, message);
+                    continue;
+                }
+
                 if !registry.has_message_id(message.id) {
                     tracing::trace!("ignoring message with unknown event id");
                     continue;
@@ -47,6 +185,11 @@ fn drain_server<E: EventPack>(world: &mut World)
                 queues.send_message(world, session_id, message.id, message.data);
             }
             InnerServerEvent::Request(request_token, request) => {
+                if server.is_unverified_client(session_id) {
+                    tracing::trace!(session_id, "rejecting request from client with unverified schema");
+                    continue;
+                }
+
                 let Some(response_event_id) = registry.get_response_id_from_request_id(request.id) else {
                     tracing::trace!(request.id, "ignoring request with unknown event id");
                     continue;
@@ -57,9 +200,42 @@ fn drain_server<E: EventPack>(world: &mut World)
         }
     }
 
+    // time out server requests that weren't resolved in time
+    // - we do this after draining so responses received this tick win
+    for (client_id, request_id, request_event_id, response_event_id) in server.expire_requests() {
+        tracing::debug!(client_id, request_id, "server request timed out");
+        queues.send_client_response(
+            world,
+            client_id,
+            request_event_id,
+            response_event_id,
+            request_id,
+            PendingClientResponseData::TimedOut,
+        );
+    }
+
+    // reject parked requests whose deadlines have expired
+    reject_expired_requests::<E>(world, &server);
+
     world.insert_resource(server);
-    world.insert_resource(queues);
     world.insert_resource(registry);
+
+    // forward messages now that the server is accessible
+    queues.forward_all(world);
+
+    world.insert_resource(queues);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) enum PendingClientResponseData
+{
+    Response(Vec<u8>),
+    Ack,
+    Reject,
+    ResponseLost,
+    Malformed,
+    TimedOut,
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -67,20 +243,210 @@ fn drain_server<E: EventPack>(world: &mut World)
 pub trait SimplenetServerEventAppExt
 {
     /// Inserts a `bevy_simplenet` server for use in the events API.
+    ///
+    /// The server is refreshed in [`RefreshSet`] in schedule `First`.
     fn insert_simplenet_server<E: EventPack>(&mut self, server: Server<EventWrapper<E>>) -> &mut Self;
+
+    /// Inserts a `bevy_simplenet` server that is refreshed in [`RefreshSet`] in the given schedule.
+    ///
+    /// Use this to align the server with a fixed-tick simulation (e.g. `FixedPreUpdate`). [`RefreshSet`] can be
+    /// ordered against your own system sets with `App::configure_sets`. Event readers only see events from the
+    /// most recent refresh, so handlers should run after [`RefreshSet`] in the same schedule.
+    fn insert_simplenet_server_in<E: EventPack>(
+        &mut self,
+        server: Server<EventWrapper<E>>,
+        schedule: impl ScheduleLabel,
+    ) -> &mut Self;
+
+    /// Sets how the server handles clients whose event schemas don't match the server's.
+    ///
+    /// Panics if the server has not been inserted.
+    fn set_simplenet_schema_policy<E: EventPack>(&mut self, policy: SchemaPolicy) -> &mut Self;
+
+    /// Spawns an entity with [`NetClient`] and [`NetConnectMsg`] components for each connected client.
+    ///
+    /// Client entities are updated when `ServerReport`s are drained in [`RefreshSet`], and can be looked up with
+    /// the [`ClientEntities`] resource. The policy controls what happens to an entity when its client
+    /// disconnects.
+    ///
+    /// Panics if client entities were already enabled.
+    fn enable_simplenet_client_entities<E: EventPack>(&mut self, policy: ClientEntityPolicy) -> &mut Self;
+
+    /// Inserts the [`ServerPendingRequests`] resource for holding request tokens with deadlines.
+    ///
+    /// Expired tokens are rejected after events are drained in [`RefreshSet`].
+    ///
+    /// Panics if pending requests were already enabled.
+    fn enable_simplenet_pending_requests<E: EventPack>(&mut self) -> &mut Self;
+
+    /// Sets the default timeout for server requests of type `Req`.
+    ///
+    /// Requests that aren't resolved within the timeout emit [`ClientResponse::TimedOut`], and late responses are
+    /// ignored. Use [`EventServer::request_with_timeout`] to override the timeout for one request.
+    ///
+    /// Panics if `Req` is not a registered server request type.
+    fn set_simplenet_server_request_timeout<E: EventPack, Req: SimplenetEvent>(
+        &mut self,
+        timeout: Duration,
+    ) -> &mut Self;
+
+    /// Triggers [`NetMsg<T>`](NetMsg) for each message of type `T` received from clients.
+    ///
+    /// Observers are triggered at the end of [`RefreshSet`] for all messages received that tick, after the
+    /// messages are available to readers. Since connection-event handlers run after [`RefreshSet`], sends from
+    /// observers will fail with [`SendError::PendingConnect`] until the connection report is read.
+    ///
+    /// Panics if `T` is not registered as a message received by the server, or if the trigger was already added.
+    fn add_simplenet_server_trigger<E: EventPack, T: SimplenetEvent + Clone>(&mut self) -> &mut Self;
+
+    /// Writes [`FromClient<T>`](FromClient) bevy events for each message of type `T` received from clients.
+    ///
+    /// Events are written at the end of [`RefreshSet`], so they can be read with `EventReader` by systems and
+    /// plugins that don't know about this crate.
+    ///
+    /// Panics if `T` is not registered as a message received by the server, or if the events were already added.
+    fn add_simplenet_server_events<E: EventPack, T: SimplenetEvent + Clone>(&mut self) -> &mut Self;
+
+    /// Sends [`ToClient<T>`](ToClient) bevy events to a client with [`EventServer::send`].
+    ///
+    /// Events are drained and sent in schedule `Last`. Events that fail to send are logged and dropped.
+    ///
+    /// Panics if `T` is not a registered message type, or if the events were already added. The events aren't
+    /// keyed by channel, so this also panics if they were added for another channel.
+    fn add_simplenet_server_send_events<E: EventPack, T: SimplenetEvent>(&mut self) -> &mut Self;
 }
 
 impl SimplenetServerEventAppExt for App
 {
     fn insert_simplenet_server<E: EventPack>(&mut self, server: Server<EventWrapper<E>>) -> &mut Self
+    {
+        self.insert_simplenet_server_in(server, First)
+    }
+
+    fn insert_simplenet_server_in<E: EventPack>(
+        &mut self,
+        server: Server<EventWrapper<E>>,
+        schedule: impl ScheduleLabel,
+    ) -> &mut Self
     {
         if self.world().contains_resource::<EventServerCore<E>>() {
             panic!("event server was already inserted");
         }
 
         self.insert_resource(EventServerCore::new(server));
+        self.init_resource::<ServerRooms<E>>();
+
+        let schedule = schedule.intern();
+        let refresh_in_first = schedule == First.intern();
+        self.add_systems(
+            schedule,
+            (move |world: &mut World| drain_server::<E>(world, refresh_in_first)).in_set(RefreshSet),
+        );
+
+        self
+    }
+
+    fn set_simplenet_schema_policy<E: EventPack>(&mut self, policy: SchemaPolicy) -> &mut Self
+    {
+        let Some(mut server) = self.world_mut().get_resource_mut::<EventServerCore<E>>() else {
+            panic!("event server is not inserted");
+        };
+        server.set_schema_policy(policy);
+
+        self
+    }
+
+    fn enable_simplenet_client_entities<E: EventPack>(&mut self, policy: ClientEntityPolicy) -> &mut Self
+    {
+        if self.world().contains_resource::<ClientEntities<E>>() {
+            panic!("client entities were already enabled");
+        }
+
+        self.insert_resource(ClientEntities::<E>::new(policy));
+
+        self
+    }
+
+    fn enable_simplenet_pending_requests<E: EventPack>(&mut self) -> &mut Self
+    {
+        if self.world().contains_resource::<ServerPendingRequests<E>>() {
+            panic!("pending requests were already enabled");
+        }
+
+        self.init_resource::<ServerPendingRequests<E>>();
+
+        self
+    }
+
+    fn set_simplenet_server_request_timeout<E: EventPack, Req: SimplenetEvent>(
+        &mut self,
+        timeout: Duration,
+    ) -> &mut Self
+    {
+        let Some(mut registry) = self.world_mut().get_resource_mut::<EventRegistry<E>>() else {
+            panic!("server request type {} is not registered", std::any::type_name::<Req>());
+        };
+        let Some((request_event_id, _)) = registry.get_server_request_ids::<Req>() else {
+            panic!("server request type {} is not registered", std::any::type_name::<Req>());
+        };
+        registry.set_server_request_timeout(request_event_id, timeout);
+
+        self
+    }
+
+    fn add_simplenet_server_trigger<E: EventPack, T: SimplenetEvent + Clone>(&mut self) -> &mut Self
+    {
+        if !self.world().contains_resource::<ServerMessageQueue<E, T>>() {
+            panic!("message type {} is not registered", std::any::type_name::<T>());
+        }
+
+        self.world_mut()
+            .resource_mut::<EventQueueConnectorServer<E>>()
+            .register_message_trigger::<T>();
+
+        self
+    }
+
+    fn add_simplenet_server_events<E: EventPack, T: SimplenetEvent + Clone>(&mut self) -> &mut Self
+    {
+        if !self.world().contains_resource::<ServerMessageQueue<E, T>>() {
+            panic!("message type {} is not registered", std::any::type_name::<T>());
+        }
+
+        self.add_event::<FromClient<T>>();
+        self.world_mut()
+            .resource_mut::<EventQueueConnectorServer<E>>()
+            .register_message_mirror::<T>();
+
+        self
+    }
+
+    fn add_simplenet_server_send_events<E: EventPack, T: SimplenetEvent>(&mut self) -> &mut Self
+    {
+        let registered = self
+            .world()
+            .get_resource::<EventRegistry<E>>()
+            .is_some_and(|registry| registry.get_message_id::<T>().is_some());
+        if !registered {
+            panic!("message type {} is not registered", std::any::type_name::<T>());
+        }
+        if let Some(bridge) = self.world().get_resource::<ToClientChannel<T>>() {
+            if bridge.channel() == std::any::type_name::<E>() {
+                panic!("{} was already added", std::any::type_name::<ToClient<T>>());
+            }
+            panic!(
+                "{} was already added for channel {}",
+                std::any::type_name::<ToClient<T>>(),
+                bridge.channel()
+            );
+        }
 
-        self.add_systems(First, drain_server::<E>.in_set(RefreshSet));
+        self.insert_resource(ToClientChannel::<T>::new::<E>());
+        self.add_event::<ToClient<T>>();
+        self.add_systems(
+            Last,
+            send_to_clients::<E, T>.run_if(resource_exists::<EventServerCore<E>>),
+        );
 
         self
     }
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use bevy_ecs::prelude::*;
+use bevy_simplenet::{ClientId, ServerReport};
+
+use crate::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Controls what happens to a client's entity when the client disconnects.
+///
+/// See [`SimplenetServerEventAppExt::enable_simplenet_client_entities`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum ClientEntityPolicy
+{
+    /// Despawn the entity. A new entity is spawned if the client reconnects.
+    #[default]
+    Despawn,
+    /// Insert [`NetDisconnected`] on the entity. The entity is reused if the client reconnects.
+    MarkDisconnected,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Component for an entity that represents a client on the server.
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct NetClient
+{
+    pub id: ClientId,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Component that stores the connect message a client sent with its most recent connection.
+#[derive(Component, Debug, Clone)]
+pub struct NetConnectMsg<E: EventPack>(pub E::ConnectMsg);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Marker component for client entities whose clients are disconnected.
+///
+/// Only used with [`ClientEntityPolicy::MarkDisconnected`].
+#[derive(Component, Debug, Copy, Clone, Default)]
+pub struct NetDisconnected;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Server resource that maps clients to their entities.
+///
+/// Client entities are spawned and updated when `ServerReport`s are drained in [`RefreshSet`].
+#[derive(Resource)]
+pub struct ClientEntities<E: EventPack>
+{
+    policy: ClientEntityPolicy,
+    /// [ client id : entity ]
+    entities: HashMap<ClientId, Entity>,
+    phantom: PhantomData<E>,
+}
+
+impl<E: EventPack> ClientEntities<E>
+{
+    pub(crate) fn new(policy: ClientEntityPolicy) -> Self
+    {
+        Self { policy, entities: HashMap::default(), phantom: PhantomData }
+    }
+
+    /// Gets the entity for a client.
+    ///
+    /// With [`ClientEntityPolicy::MarkDisconnected`], this includes entities of disconnected clients.
+    pub fn get(&self, client_id: ClientId) -> Option<Entity>
+    {
+        self.entities.get(&client_id).copied()
+    }
+
+    /// Iterates all client entities.
+    pub fn iter(&self) -> impl Iterator<Item = (ClientId, Entity)> + '_
+    {
+        self.entities
+            .iter()
+            .map(|(client_id, entity)| (*client_id, *entity))
+    }
+
+    /// Accesses the disconnect policy.
+    pub fn policy(&self) -> ClientEntityPolicy
+    {
+        self.policy
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Updates a client's entity in response to a connection report.
+///
+/// Does nothing if client entities are not enabled.
+pub(crate) fn update_client_entity<E: EventPack>(
+    world: &mut World,
+    client_id: ClientId,
+    report: &ServerReport<E::ConnectMsg>,
+)
+{
+    let Some(mut entities) = world.remove_resource::<ClientEntities<E>>() else {
+        return;
+    };
+
+    match report {
+        ServerReport::Connected(_, connect_msg) => {
+            let connect_msg = NetConnectMsg::<E>(connect_msg.clone());
+            let existing = entities
+                .get(client_id)
+                .and_then(|entity| world.get_entity_mut(entity).ok());
+
+            match existing {
+                Some(mut entity) => {
+                    entity.remove::<NetDisconnected>().insert(connect_msg);
+                }
+                None => {
+                    let entity = world.spawn((NetClient { id: client_id }, connect_msg)).id();
+                    entities.entities.insert(client_id, entity);
+                }
+            }
+        }
+        ServerReport::Disconnected => match entities.policy {
+            ClientEntityPolicy::Despawn => {
+                if let Some(entity) = entities.entities.remove(&client_id) {
+                    if let Ok(entity) = world.get_entity_mut(entity) {
+                        entity.despawn();
+                    }
+                }
+            }
+            ClientEntityPolicy::MarkDisconnected => {
+                if let Some(mut entity) = entities
+                    .get(client_id)
+                    .and_then(|entity| world.get_entity_mut(entity).ok())
+                {
+                    entity.insert(NetDisconnected);
+                }
+            }
+        },
+    }
+
+    world.insert_resource(entities);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
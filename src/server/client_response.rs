@@ -0,0 +1,67 @@
+use crate::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Client response for a server request.
+pub enum ClientResponse<T: SimplenetEvent>
+{
+    /// Response from the client.
+    Response(T, u64),
+    /// Request is acknowledged. No response will be sent.
+    Ack(u64),
+    /// Request is rejected. No response will be sent.
+    Reject(u64),
+    /// The client disconnected before the server received a response.
+    ResponseLost(u64),
+    /// The client sent a response that doesn't match the request, or that failed to deserialize.
+    ///
+    /// See [`ServerDecodeErrorReader`].
+    Malformed(u64),
+    /// The request's timeout elapsed before the client responded. A late response will be ignored.
+    TimedOut(u64),
+}
+
+impl<T: SimplenetEvent> ClientResponse<T>
+{
+    /// Accesses the internal response if self is [`ClientResponse::Response`].
+    pub fn response(&self) -> Option<&T>
+    {
+        match self {
+            Self::Response(response, _) => Some(response),
+            _ => None,
+        }
+    }
+
+    /// Assesses the response's original request id.
+    pub fn request_id(&self) -> u64
+    {
+        match self {
+            Self::Response(_, request_id) => *request_id,
+            Self::Ack(request_id) => *request_id,
+            Self::Reject(request_id) => *request_id,
+            Self::ResponseLost(request_id) => *request_id,
+            Self::Malformed(request_id) => *request_id,
+            Self::TimedOut(request_id) => *request_id,
+        }
+    }
+}
+
+impl<T: SimplenetEvent + Eq + PartialEq> Eq for ClientResponse<T> {}
+
+impl<T: SimplenetEvent + Eq + PartialEq> PartialEq for ClientResponse<T>
+{
+    fn eq(&self, other: &Self) -> bool
+    {
+        match (self, other) {
+            (Self::Response(l0, l1), Self::Response(r0, r1)) => l0 == r0 && l1 == r1,
+            (Self::Ack(l0), Self::Ack(r0)) => l0 == r0,
+            (Self::Reject(l0), Self::Reject(r0)) => l0 == r0,
+            (Self::ResponseLost(l0), Self::ResponseLost(r0)) => l0 == r0,
+            (Self::Malformed(l0), Self::Malformed(r0)) => l0 == r0,
+            (Self::TimedOut(l0), Self::TimedOut(r0)) => l0 == r0,
+            _ => false,
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,77 @@
+use std::marker::PhantomData;
+
+use bevy_ecs::prelude::*;
+use bevy_simplenet::ClientId;
+
+use crate::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Bevy event written on the server for each client message of type `T`.
+///
+/// Enable with [`SimplenetServerEventAppExt::add_simplenet_server_events`].
+#[derive(Event, Debug, Clone)]
+pub struct FromClient<T: SimplenetEvent>
+{
+    /// The client that sent the message.
+    pub client_id: ClientId,
+    /// The message.
+    pub message: T,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Bevy event that is sent to a client with [`EventServer::send`].
+///
+/// Enable with [`SimplenetServerEventAppExt::add_simplenet_server_send_events`].
+#[derive(Event, Debug, Clone)]
+pub struct ToClient<T: SimplenetEvent>
+{
+    /// The target client.
+    pub client_id: ClientId,
+    /// The message.
+    pub message: T,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Records the channel that [`ToClient<T>`](ToClient) events are sent through.
+///
+/// The events aren't keyed by channel, so they can only be sent through one.
+#[derive(Resource)]
+pub(crate) struct ToClientChannel<T: SimplenetEvent>
+{
+    channel: &'static str,
+    phantom: PhantomData<T>,
+}
+
+impl<T: SimplenetEvent> ToClientChannel<T>
+{
+    pub(crate) fn new<E: EventPack>() -> Self
+    {
+        Self { channel: std::any::type_name::<E>(), phantom: PhantomData }
+    }
+
+    /// Gets the name of the channel.
+    pub(crate) fn channel(&self) -> &'static str
+    {
+        self.channel
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Drains [`ToClient`] events and sends them to clients.
+pub(crate) fn send_to_clients<E: EventPack, T: SimplenetEvent>(
+    mut events: ResMut<Events<ToClient<T>>>,
+    server: EventServer<E>,
+)
+{
+    for ToClient { client_id, message } in events.drain() {
+        if let Err(err) = server.send(client_id, message) {
+            tracing::warn!(client_id, ?err, "failed sending ToClient event");
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -1,3 +1,4 @@
+use std::any::TypeId;
 use std::collections::HashMap;
 use std::marker::PhantomData;
 
@@ -5,12 +6,21 @@ use bevy_cobweb::prelude::*;
 use bevy_ecs::prelude::*;
 use bevy_ecs::world::Command;
 use bevy_simplenet::{ClientId, RequestToken, ServerReport};
-use bincode::Options;
 
 use crate::*;
 
 //-------------------------------------------------------------------------------------------------------------------
 
+pub(crate) struct PendingClientResponse
+{
+    client_id: ClientId,
+    response_event_id: u16,
+    request_id: u64,
+    data: PendingClientResponseData,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 fn clear_connection_queue<E: EventPack>(mut queue: ResMut<ServerConnectionQueue<E>>)
 {
     queue.clear();
@@ -18,15 +28,36 @@ fn clear_connection_queue<E: EventPack>(mut queue: ResMut<ServerConnectionQueue<
 
 //-------------------------------------------------------------------------------------------------------------------
 
+fn clear_decode_error_queue<E: EventPack>(mut queue: ResMut<ServerDecodeErrorQueue<E>>)
+{
+    queue.clear();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn clear_cancel_queue<E: EventPack>(mut queue: ResMut<ServerCancelQueue<E>>)
+{
+    queue.clear();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 fn clear_message_queue<E: EventPack, T: SimplenetEvent>(
-    In(client_id): In<Option<ClientId>>,
+    In(swap_buffers): In<bool>,
     mut queue: ResMut<ServerMessageQueue<E, T>>,
 )
 {
-    match client_id {
-        Some(client_id) => queue.clear_session(client_id),
-        None => queue.clear(),
-    }
+    queue.clear(swap_buffers);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn clear_message_session<E: EventPack, T: SimplenetEvent>(
+    In(client_id): In<ClientId>,
+    mut queue: ResMut<ServerMessageQueue<E, T>>,
+)
+{
+    queue.clear_session(client_id);
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -44,6 +75,25 @@ fn clear_request_queue<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>(
 
 //-------------------------------------------------------------------------------------------------------------------
 
+fn clear_response_queue<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>(
+    mut queue: ResMut<ServerResponseQueue<E, Req, Resp>>,
+)
+{
+    queue.clear();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn reset_response_queue<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>(
+    In(client_id): In<ClientId>,
+    mut queue: ResMut<ServerResponseQueue<E, Req, Resp>>,
+)
+{
+    queue.reset(client_id);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 fn send_connection<E: EventPack>(
     In((counter, client_id, report)): In<(u32, ClientId, ServerReport<E::ConnectMsg>)>,
     mut queue: ResMut<ServerConnectionQueue<E>>,
@@ -54,14 +104,29 @@ fn send_connection<E: EventPack>(
 
 //-------------------------------------------------------------------------------------------------------------------
 
-fn send_message<E: EventPack, T: SimplenetEvent>(
-    In((client_id, data)): In<(ClientId, Vec<u8>)>,
+fn send_schema_mismatch<E: EventPack>(
+    In((client_id, mismatch)): In<(ClientId, SchemaMismatch)>,
+    mut queue: ResMut<ServerConnectionQueue<E>>,
+)
+{
+    queue.send_schema_mismatch(client_id, mismatch);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn send_message<E: EventPack, T: SimplenetEvent, C: EventCodec>(
+    In((client_id, message_event_id, data)): In<(ClientId, u16, Vec<u8>)>,
     mut queue: ResMut<ServerMessageQueue<E, T>>,
+    mut errors: ResMut<ServerDecodeErrorQueue<E>>,
 )
 {
-    let Ok(message) = bincode::DefaultOptions::new().deserialize(&data[..]) else {
-        tracing::warn!("received client message that failed to deserialize");
-        return;
+    let message = match C::decode(&data[..]) {
+        Ok(message) => message,
+        Err(err) => {
+            tracing::warn!(client_id, message_event_id, "received client message that failed to deserialize");
+            errors.send(client_id, DecodeError::new::<T>(message_event_id, err));
+            return;
+        }
     };
 
     queue.send(client_id, message);
@@ -69,14 +134,21 @@ fn send_message<E: EventPack, T: SimplenetEvent>(
 
 //-------------------------------------------------------------------------------------------------------------------
 
-fn send_request<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>(
-    In((request_token, data)): In<(RequestToken, Vec<u8>)>,
+fn send_request<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent, C: EventCodec>(
+    In((request_event_id, request_token, data)): In<(u16, RequestToken, Vec<u8>)>,
     mut queue: ResMut<ServerRequestQueue<E, Req, Resp>>,
+    mut errors: ResMut<ServerDecodeErrorQueue<E>>,
 )
 {
-    let Ok(request) = bincode::DefaultOptions::new().deserialize(&data[..]) else {
-        tracing::warn!("received client request that failed to deserialize");
-        return;
+    let request = match C::decode(&data[..]) {
+        Ok(request) => request,
+        Err(err) => {
+            // note: dropping the token rejects the request
+            let client_id = request_token.client_id();
+            tracing::warn!(client_id, request_event_id, "received client request that failed to deserialize");
+            errors.send(client_id, DecodeError::new::<Req>(request_event_id, err));
+            return;
+        }
     };
 
     queue.send(request_token, request);
@@ -84,32 +156,111 @@ fn send_request<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>(
 
 //-------------------------------------------------------------------------------------------------------------------
 
+fn send_client_response<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent, C: EventCodec>(
+    In(response): In<PendingClientResponse>,
+    mut queue: ResMut<ServerResponseQueue<E, Req, Resp>>,
+    mut errors: ResMut<ServerDecodeErrorQueue<E>>,
+)
+{
+    let client_id = response.client_id;
+    let request_id = response.request_id;
+    let response = match response.data {
+        PendingClientResponseData::Response(data) => match C::decode(&data[..]) {
+            Ok(resp) => ClientResponse::<Resp>::Response(resp, request_id),
+            Err(err) => {
+                tracing::warn!(client_id, request_id, "received client response that failed to deserialize");
+                errors.send(client_id, DecodeError::new::<Resp>(response.response_event_id, err));
+                ClientResponse::<Resp>::Malformed(request_id)
+            }
+        },
+        PendingClientResponseData::Ack => ClientResponse::Ack(request_id),
+        PendingClientResponseData::Reject => ClientResponse::Reject(request_id),
+        PendingClientResponseData::ResponseLost => ClientResponse::ResponseLost(request_id),
+        PendingClientResponseData::Malformed => ClientResponse::Malformed(request_id),
+        PendingClientResponseData::TimedOut => ClientResponse::TimedOut(request_id),
+    };
+
+    queue.send(client_id, response);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+type SendRequestCallback = CallbackWith<(), (RequestToken, Vec<u8>)>;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn trigger_messages<E: EventPack, T: SimplenetEvent + Clone>(
+    mut commands: Commands,
+    queue: Res<ServerMessageQueue<E, T>>,
+)
+{
+    for (client_id, message) in queue.iter() {
+        commands.trigger(NetMsg { client_id: *client_id, message: message.clone() });
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn mirror_messages<E: EventPack, T: SimplenetEvent + Clone>(
+    mut events: EventWriter<FromClient<T>>,
+    queue: Res<ServerMessageQueue<E, T>>,
+)
+{
+    events.send_batch(
+        queue
+            .iter()
+            .map(|(client_id, message)| FromClient { client_id: *client_id, message: message.clone() }),
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Provides access to registered event queues.
 #[derive(Resource)]
 pub(crate) struct EventQueueConnectorServer<E: EventPack>
 {
     /// Cached systems for clearing event queues.
-    clear_message_queues: Vec<CallbackWith<(), Option<ClientId>>>,
+    clear_message_queues: Vec<CallbackWith<(), bool>>,
     clear_request_queues: Vec<CallbackWith<(), Option<ClientId>>>,
+    clear_response_queues: Vec<Callback<()>>,
+
+    /// Cached systems for discarding messages from old sessions.
+    clear_message_sessions: Vec<CallbackWith<(), ClientId>>,
+
+    /// Cached systems for resetting stale client responses.
+    reset_response_queues: Vec<CallbackWith<(), ClientId>>,
 
     /// Cached systems for sending message events.
     /// [ message event id : callback ]
     send_messages: HashMap<u16, CallbackWith<(), (ClientId, Vec<u8>)>>,
     /// Cached systems for sending response events.
     /// [ response event id : [ request event id : callback ] ]
-    send_requests: HashMap<u16, HashMap<u16, CallbackWith<(), (RequestToken, Vec<u8>)>>>,
+    send_requests: HashMap<u16, HashMap<u16, SendRequestCallback>>,
+    /// Cached systems for sending client response events.
+    /// [ response event id : [ request event id : callback ] ]
+    send_client_responses: HashMap<u16, HashMap<u16, CallbackWith<(), PendingClientResponse>>>,
+
+    /// Cached systems for forwarding messages to observers and bevy events.
+    /// [ (forwarded event type id, callback) ]
+    message_forwarders: Vec<(TypeId, Callback<()>)>,
 
     phantom: PhantomData<E>,
 }
 
 impl<E: EventPack> EventQueueConnectorServer<E>
 {
-    pub(crate) fn register_message<T: SimplenetEvent>(&mut self, message_event_id: u16)
+    pub(crate) fn register_message<T: SimplenetEvent, C: EventCodec>(&mut self, message_event_id: u16)
     {
         // add clear-message
         self.clear_message_queues
-            .push(CallbackWith::new(|world: &mut World, target: Option<ClientId>| {
-                syscall(world, target, clear_message_queue::<E, T>);
+            .push(CallbackWith::new(|world: &mut World, swap_buffers: bool| {
+                syscall(world, swap_buffers, clear_message_queue::<E, T>);
+            }));
+
+        // add clear-session
+        self.clear_message_sessions
+            .push(CallbackWith::new(|world: &mut World, client_id: ClientId| {
+                syscall(world, client_id, clear_message_session::<E, T>);
             }));
 
         // add send-message
@@ -117,8 +268,8 @@ impl<E: EventPack> EventQueueConnectorServer<E>
             .send_messages
             .insert(
                 message_event_id,
-                CallbackWith::new(|world: &mut World, package: (ClientId, Vec<u8>)| {
-                    syscall(world, package, send_message::<E, T>);
+                CallbackWith::new(move |world: &mut World, (client_id, data): (ClientId, Vec<u8>)| {
+                    syscall(world, (client_id, message_event_id, data), send_message::<E, T, C>);
                 }),
             )
             .is_some()
@@ -127,7 +278,7 @@ impl<E: EventPack> EventQueueConnectorServer<E>
         }
     }
 
-    pub(crate) fn register_request<Req: SimplenetEvent, Resp: SimplenetEvent>(
+    pub(crate) fn register_request<Req: SimplenetEvent, Resp: SimplenetEvent, C: EventCodec>(
         &mut self,
         request_event_id: u16,
         response_event_id: u16,
@@ -146,8 +297,8 @@ impl<E: EventPack> EventQueueConnectorServer<E>
             .or_default()
             .insert(
                 request_event_id,
-                CallbackWith::new(|world: &mut World, package: (RequestToken, Vec<u8>)| {
-                    syscall(world, package, send_request::<E, Req, Resp>);
+                CallbackWith::new(move |world: &mut World, (token, data): (RequestToken, Vec<u8>)| {
+                    syscall(world, (request_event_id, token, data), send_request::<E, Req, Resp, C>);
                 }),
             )
             .is_some()
@@ -156,20 +307,97 @@ impl<E: EventPack> EventQueueConnectorServer<E>
         }
     }
 
-    pub(crate) fn clear_all(&self, world: &mut World)
+    pub(crate) fn register_client_response<Req: SimplenetEvent, Resp: SimplenetEvent, C: EventCodec>(
+        &mut self,
+        request_event_id: u16,
+        response_event_id: u16,
+    )
+    {
+        // add clear-response
+        self.clear_response_queues
+            .push(Callback::new(|world: &mut World| {
+                syscall(world, (), clear_response_queue::<E, Req, Resp>);
+            }));
+
+        // add reset-response
+        self.reset_response_queues
+            .push(CallbackWith::new(|world: &mut World, client_id: ClientId| {
+                syscall(world, client_id, reset_response_queue::<E, Req, Resp>);
+            }));
+
+        // add send-response
+        if self
+            .send_client_responses
+            .entry(response_event_id)
+            .or_default()
+            .insert(
+                request_event_id,
+                CallbackWith::new(|world: &mut World, response: PendingClientResponse| {
+                    syscall(world, response, send_client_response::<E, Req, Resp, C>);
+                }),
+            )
+            .is_some()
+        {
+            panic!("server request/response was already registered");
+        }
+    }
+
+    fn register_message_forwarder<F: 'static>(&mut self, callback: Callback<()>)
+    {
+        let type_id = TypeId::of::<F>();
+        if self.message_forwarders.iter().any(|(id, _)| *id == type_id) {
+            panic!("{} forwarding was already added", std::any::type_name::<F>());
+        }
+
+        self.message_forwarders.push((type_id, callback));
+    }
+
+    pub(crate) fn register_message_trigger<T: SimplenetEvent + Clone>(&mut self)
+    {
+        self.register_message_forwarder::<NetMsg<T>>(Callback::new(|world: &mut World| {
+            syscall(world, (), trigger_messages::<E, T>);
+        }));
+    }
+
+    pub(crate) fn register_message_mirror<T: SimplenetEvent + Clone>(&mut self)
+    {
+        self.register_message_forwarder::<FromClient<T>>(Callback::new(|world: &mut World| {
+            syscall(world, (), mirror_messages::<E, T>);
+        }));
+    }
+
+    pub(crate) fn clear_all(&self, world: &mut World, swap_buffers: bool)
     {
         // clear connection events
         world.syscall((), clear_connection_queue::<E>);
 
+        // clear decode errors
+        world.syscall((), clear_decode_error_queue::<E>);
+
+        // clear cancellations
+        world.syscall((), clear_cancel_queue::<E>);
+
         // clear messages
         for cb in self.clear_message_queues.iter() {
-            cb.call_with(None).apply(world);
+            cb.call_with(swap_buffers).apply(world);
         }
 
         // clear requests
         for cb in self.clear_request_queues.iter() {
             cb.call_with(None).apply(world);
         }
+
+        // clear client responses
+        for cb in self.clear_response_queues.iter() {
+            cb.clone().apply(world);
+        }
+    }
+
+    pub(crate) fn forward_all(&self, world: &mut World)
+    {
+        for (_, cb) in self.message_forwarders.iter() {
+            cb.clone().apply(world);
+        }
     }
 
     pub(crate) fn handle_disconnect(&self, world: &mut World, client_id: ClientId)
@@ -177,14 +405,19 @@ impl<E: EventPack> EventQueueConnectorServer<E>
         tracing::trace!(client_id, "clearing server queues on disconnect");
 
         // clear messages for this client
-        for cb in self.clear_message_queues.iter() {
-            cb.call_with(Some(client_id)).apply(world);
+        for cb in self.clear_message_sessions.iter() {
+            cb.call_with(client_id).apply(world);
         }
 
         // clear requests for this client
         for cb in self.clear_request_queues.iter() {
             cb.call_with(Some(client_id)).apply(world);
         }
+
+        // replace Response/Ack with ResponseLost for this client
+        for cb in self.reset_response_queues.iter() {
+            cb.call_with(client_id).apply(world);
+        }
     }
 
     pub(crate) fn send_connection(
@@ -198,6 +431,11 @@ impl<E: EventPack> EventQueueConnectorServer<E>
         world.syscall((counter, client_id, report), send_connection::<E>);
     }
 
+    pub(crate) fn send_schema_mismatch(&self, world: &mut World, client_id: ClientId, mismatch: SchemaMismatch)
+    {
+        world.syscall((client_id, mismatch), send_schema_mismatch::<E>);
+    }
+
     pub(crate) fn send_message(&self, world: &mut World, client_id: ClientId, message_event_id: u16, data: Vec<u8>)
     {
         let Some(cb) = self.send_messages.get(&message_event_id) else {
@@ -229,6 +467,30 @@ impl<E: EventPack> EventQueueConnectorServer<E>
 
         cb.call_with((request_token, data)).apply(world);
     }
+
+    pub(crate) fn send_client_response(
+        &self,
+        world: &mut World,
+        client_id: ClientId,
+        request_event_id: u16,
+        response_event_id: u16,
+        request_id: u64,
+        data: PendingClientResponseData,
+    )
+    {
+        let Some(request_map) = self.send_client_responses.get(&response_event_id) else {
+            tracing::error!("tried to send client response of unregistered response type");
+            return;
+        };
+
+        let Some(cb) = request_map.get(&request_event_id) else {
+            tracing::error!("tried to send client response for unregistered request type");
+            return;
+        };
+
+        cb.call_with(PendingClientResponse { client_id, response_event_id, request_id, data })
+            .apply(world);
+    }
 }
 
 impl<E: EventPack> Default for EventQueueConnectorServer<E>
@@ -238,9 +500,14 @@ impl<E: EventPack> Default for EventQueueConnectorServer<E>
         Self {
             clear_message_queues: Vec::default(),
             clear_request_queues: Vec::default(),
+            clear_response_queues: Vec::default(),
+            clear_message_sessions: Vec::default(),
+            reset_response_queues: Vec::default(),
             send_messages: HashMap::default(),
             send_requests: HashMap::default(),
-            phantom: PhantomData::default(),
+            send_client_responses: HashMap::default(),
+            message_forwarders: Vec::default(),
+            phantom: PhantomData,
         }
     }
 }
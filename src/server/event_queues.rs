@@ -13,6 +13,8 @@ pub(crate) struct ServerConnectionQueue<E: EventPack>
     /// Includes event counter for use in synchronizing with [`EventServer::send`] the first time a
     /// `ServerReport::Connected` is iterated over.
     queue: Vec<(u32, ClientId, ServerReport<E::ConnectMsg>)>,
+    /// Clients whose event schemas don't match the server's.
+    mismatches: Vec<(ClientId, SchemaMismatch)>,
 }
 
 impl<E: EventPack> ServerConnectionQueue<E>
@@ -20,6 +22,7 @@ impl<E: EventPack> ServerConnectionQueue<E>
     pub(crate) fn clear(&mut self)
     {
         self.queue.clear();
+        self.mismatches.clear();
     }
 
     pub(crate) fn send(&mut self, counter: u32, client_id: ClientId, report: ServerReport<E::ConnectMsg>)
@@ -31,53 +34,130 @@ impl<E: EventPack> ServerConnectionQueue<E>
     {
         self.queue.iter()
     }
+
+    pub(crate) fn send_schema_mismatch(&mut self, client_id: ClientId, mismatch: SchemaMismatch)
+    {
+        self.mismatches.push((client_id, mismatch));
+    }
+
+    pub(crate) fn iter_schema_mismatches(&self) -> impl Iterator<Item = &(ClientId, SchemaMismatch)> + '_
+    {
+        self.mismatches.iter()
+    }
 }
 
 impl<E: EventPack> Default for ServerConnectionQueue<E>
 {
     fn default() -> Self
     {
-        Self { queue: Vec::default() }
+        Self { queue: Vec::default(), mismatches: Vec::default() }
     }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
 #[derive(Resource)]
-pub(crate) struct ServerMessageQueue<E: EventPack, T: SimplenetEvent>
+pub(crate) struct ServerDecodeErrorQueue<E: EventPack>
 {
-    queue: Vec<Option<(ClientId, T)>>,
+    queue: Vec<(ClientId, DecodeError)>,
     phantom: PhantomData<E>,
 }
 
-impl<E: EventPack, T: SimplenetEvent> ServerMessageQueue<E, T>
+impl<E: EventPack> ServerDecodeErrorQueue<E>
+{
+    pub(crate) fn clear(&mut self)
+    {
+        self.queue.clear();
+    }
+
+    pub(crate) fn send(&mut self, client_id: ClientId, error: DecodeError)
+    {
+        self.queue.push((client_id, error));
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &(ClientId, DecodeError)> + '_
+    {
+        self.queue.iter()
+    }
+}
+
+impl<E: EventPack> Default for ServerDecodeErrorQueue<E>
+{
+    fn default() -> Self
+    {
+        Self { queue: Vec::default(), phantom: PhantomData }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Resource)]
+pub(crate) struct ServerCancelQueue<E: EventPack>
+{
+    queue: Vec<(ClientId, u64)>,
+    phantom: PhantomData<E>,
+}
+
+impl<E: EventPack> ServerCancelQueue<E>
 {
     pub(crate) fn clear(&mut self)
     {
         self.queue.clear();
     }
 
+    pub(crate) fn send(&mut self, client_id: ClientId, request_id: u64)
+    {
+        self.queue.push((client_id, request_id));
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &(ClientId, u64)> + '_
+    {
+        self.queue.iter()
+    }
+}
+
+impl<E: EventPack> Default for ServerCancelQueue<E>
+{
+    fn default() -> Self
+    {
+        Self { queue: Vec::default(), phantom: PhantomData }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Resource)]
+pub(crate) struct ServerMessageQueue<E: EventPack, T: SimplenetEvent>
+{
+    queue: EventBuffer<(ClientId, T)>,
+    phantom: PhantomData<E>,
+}
+
+impl<E: EventPack, T: SimplenetEvent> ServerMessageQueue<E, T>
+{
+    pub(crate) fn clear(&mut self, swap: bool)
+    {
+        self.queue.update(swap);
+    }
+
     pub(crate) fn clear_session(&mut self, client_id: ClientId)
     {
-        self.queue
-            .iter_mut()
-            .filter(|i| match i {
-                Some(i) => i.0 == client_id,
-                None => false,
-            })
-            .for_each(|i| {
-                *i = None;
-            });
+        self.queue.discard(|(id, _)| *id == client_id);
     }
 
     pub(crate) fn send(&mut self, client_id: ClientId, message: T)
     {
-        self.queue.push(Some((client_id, message)));
+        self.queue.send((client_id, message));
     }
 
     pub(crate) fn iter(&self) -> impl Iterator<Item = &(ClientId, T)> + '_
     {
-        self.queue.iter().filter_map(|i| i.as_ref())
+        self.queue.iter()
+    }
+
+    pub(crate) fn read(&self, cursor: &mut usize) -> impl Iterator<Item = &(ClientId, T)> + '_
+    {
+        self.queue.read(cursor)
     }
 }
 
@@ -85,7 +165,7 @@ impl<E: EventPack, T: SimplenetEvent> Default for ServerMessageQueue<E, T>
 {
     fn default() -> Self
     {
-        Self { queue: Vec::default(), phantom: PhantomData::default() }
+        Self { queue: EventBuffer::default(), phantom: PhantomData }
     }
 }
 
@@ -125,7 +205,7 @@ impl<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent> ServerRequestQueue
 
     pub(crate) fn drain(&mut self) -> impl Iterator<Item = (RequestToken, Req)> + '_
     {
-        self.queue.drain(..).filter_map(|i| i)
+        self.queue.drain(..).flatten()
     }
 }
 
@@ -133,7 +213,54 @@ impl<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent> Default for Server
 {
     fn default() -> Self
     {
-        Self { queue: Vec::default(), phantom: PhantomData::default() }
+        Self { queue: Vec::default(), phantom: PhantomData }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Resource)]
+pub(crate) struct ServerResponseQueue<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>
+{
+    queue: Vec<(ClientId, ClientResponse<Resp>)>,
+    phantom: PhantomData<(E, Req)>,
+}
+
+impl<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent> ServerResponseQueue<E, Req, Resp>
+{
+    pub(crate) fn clear(&mut self)
+    {
+        self.queue.clear();
+    }
+
+    pub(crate) fn reset(&mut self, client_id: ClientId)
+    {
+        for (id, response) in self.queue.iter_mut().filter(|(id, _)| *id == client_id) {
+            let request_id = match response {
+                ClientResponse::Response(_, request_id) | ClientResponse::Ack(request_id) => *request_id,
+                _ => continue,
+            };
+            tracing::warn!(client_id = id, "'losing' client response older than a recent disconnect");
+            *response = ClientResponse::<Resp>::ResponseLost(request_id);
+        }
+    }
+
+    pub(crate) fn send(&mut self, client_id: ClientId, response: ClientResponse<Resp>)
+    {
+        self.queue.push((client_id, response));
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &(ClientId, ClientResponse<Resp>)> + '_
+    {
+        self.queue.iter()
+    }
+}
+
+impl<E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent> Default for ServerResponseQueue<E, Req, Resp>
+{
+    fn default() -> Self
+    {
+        Self { queue: Vec::default(), phantom: PhantomData }
     }
 }
 
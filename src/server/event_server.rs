@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use bevy_ecs::prelude::*;
 use bevy_ecs::system::SystemParam;
 use bevy_simplenet::{ClientId, CloseFrame, RequestToken};
@@ -19,19 +21,136 @@ impl<'w, E: EventPack> EventServer<'w, E>
     /// Sends a message to a client.
     ///
     /// This will fail if there is a pending `ServerReport::Connected` that hasn't been read by any systems.
-    pub fn send<T: SimplenetEvent>(&self, client_id: ClientId, message: T)
+    pub fn send<T: SimplenetEvent>(&self, client_id: ClientId, message: T) -> Result<(), SendError>
     {
         self.server.send(&self.registry, client_id, message)
     }
 
+    /// Sends a message to multiple clients.
+    ///
+    /// The message is serialized once and reused for every client. Clients are skipped if they aren't connected or
+    /// have a pending `ServerReport::Connected` that hasn't been read by any systems.
+    ///
+    /// Returns [`SendManyError::Message`] if the message could not be serialized, or [`SendManyError::Clients`]
+    /// with the skipped clients. The message is still sent to all other clients.
+    pub fn send_many<T: SimplenetEvent>(
+        &self,
+        client_ids: impl IntoIterator<Item = ClientId>,
+        message: T,
+    ) -> Result<(), SendManyError>
+    {
+        self.server.send_many(&self.registry, client_ids, message)
+    }
+
+    /// Sends a message to all connected clients.
+    ///
+    /// Clients with a pending `ServerReport::Connected` that hasn't been read by any systems are not targeted.
+    ///
+    /// See [`Self::send_many`].
+    pub fn broadcast<T: SimplenetEvent>(&self, message: T) -> Result<(), SendManyError>
+    {
+        self.server
+            .send_many(&self.registry, self.server.connected_clients(), message)
+    }
+
+    /// Sends a message to all connected clients except `excluded`.
+    ///
+    /// See [`Self::broadcast`].
+    pub fn broadcast_except<T: SimplenetEvent>(&self, excluded: ClientId, message: T)
+        -> Result<(), SendManyError>
+    {
+        self.server.send_many(
+            &self.registry,
+            self.server
+                .connected_clients()
+                .filter(|client_id| *client_id != excluded),
+            message,
+        )
+    }
+
+    /// Sends a message to all members of a room.
+    ///
+    /// See [`Self::send_many`].
+    pub fn send_room<T: SimplenetEvent>(
+        &self,
+        rooms: &ServerRooms<E>,
+        room_id: RoomId,
+        message: T,
+    ) -> Result<(), SendManyError>
+    {
+        self.server
+            .send_many(&self.registry, rooms.members(room_id), message)
+    }
+
     /// Responds to a client request.
-    pub fn respond<Resp: SimplenetEvent>(&self, token: RequestToken, response: Resp)
+    ///
+    /// The request is rejected if the response fails to send.
+    pub fn respond<Resp: SimplenetEvent>(&self, token: RequestToken, response: Resp) -> Result<(), SendError>
     {
         self.server.respond(&self.registry, token, response)
     }
 
+    /// Sends a chunk for a client's stream request.
+    ///
+    /// Chunks are received by the client in order, followed by the stream's terminal event. End the stream with
+    /// [`Self::end_stream`] or [`Self::fail_stream`].
+    ///
+    /// Returns [`SendError::StaleRequest`] if the client disconnected after sending the request.
+    pub fn send_chunk<Chunk: SimplenetEvent>(&self, token: &RequestToken, chunk: Chunk) -> Result<(), SendError>
+    {
+        self.server.send_chunk(&self.registry, token, chunk)
+    }
+
+    /// Ends a client's stream request, resolving it as `StreamResponse::Done` on the client.
+    ///
+    /// The stream is failed if this fails to send.
+    pub fn end_stream(&self, token: RequestToken) -> Result<(), SendError>
+    {
+        self.server.ack(token)
+    }
+
+    /// Fails a client's stream request, resolving it as `StreamResponse::Error` on the client.
+    ///
+    /// The stream is still failed if this fails to send. Dropping the token also fails the stream, but chunks sent
+    /// before the token was dropped may be discarded by the client.
+    pub fn fail_stream(&self, token: RequestToken) -> Result<(), SendError>
+    {
+        self.server.fail_stream(token)
+    }
+
+    /// Sends a request to a client.
+    ///
+    /// Returns the request id. The client's response can be read with [`ServerResponseReader`]. If the request is
+    /// sent, then a [`ClientResponse`] of some kind will be emitted for it. Requests without a timeout stay
+    /// pending until the client responds or disconnects, see
+    /// [`SimplenetServerEventAppExt::set_simplenet_server_request_timeout`].
+    ///
+    /// This will fail if there is a pending `ServerReport::Connected` that hasn't been read by any systems.
+    pub fn request<Req: SimplenetEvent>(&self, client_id: ClientId, request: Req) -> Result<u64, SendError>
+    {
+        self.server
+            .request(&self.registry, client_id, request, None)
+    }
+
+    /// Sends a request to a client that times out if it isn't resolved within `timeout`.
+    ///
+    /// Overrides the request type's default timeout. See [`Self::request`] and
+    /// [`SimplenetServerEventAppExt::set_simplenet_server_request_timeout`].
+    pub fn request_with_timeout<Req: SimplenetEvent>(
+        &self,
+        client_id: ClientId,
+        request: Req,
+        timeout: Duration,
+    ) -> Result<u64, SendError>
+    {
+        self.server
+            .request(&self.registry, client_id, request, Some(timeout))
+    }
+
     /// Acknowledges a client request.
-    pub fn ack(&self, token: RequestToken)
+    ///
+    /// The request is rejected if the ack fails to send.
+    pub fn ack(&self, token: RequestToken) -> Result<(), SendError>
     {
         self.server.ack(token)
     }
@@ -1,15 +1,24 @@
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use bevy_ecs::prelude::*;
 use bevy_simplenet::*;
-use bincode::Options;
+use crossbeam::channel::{Receiver, Sender};
 
 use crate::*;
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// A client's connect report and its event counter.
+type HeldConnect<E> = (u32, ServerReport<<E as EventPack>::ConnectMsg>);
+
+/// A new server request: (request id, (client id, request event id, response event id), deadline).
+type NewRequest = (u64, (ClientId, u16, u16), Option<Instant>);
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Event server resource that owns the internal `bevy_simplenet` server.
 #[derive(Resource)]
 pub(crate) struct EventServerCore<E: EventPack>
@@ -23,6 +32,24 @@ pub(crate) struct EventServerCore<E: EventPack>
     /// Tracks the most recent un-consumed connection messages for each client.
     /// A value > u32::MAX is equivalent to None.
     pending_connect: HashMap<ClientId, Arc<AtomicU64>>,
+
+    /// How to handle clients with mismatched event schemas.
+    schema_policy: SchemaPolicy,
+    /// Clients whose connect reports are held until their event schemas are verified.
+    /// Only used with [`SchemaPolicy::Disconnect`].
+    /// [ client id : (event counter, connect report), or `None` if the client's schema didn't match ]
+    unverified_clients: HashMap<ClientId, Option<HeldConnect<E>>>,
+
+    /// Source of server request ids.
+    request_counter: AtomicU64,
+
+    /// Tracks server requests that haven't been resolved by clients.
+    request_sender: Sender<NewRequest>,
+    request_receiver: Receiver<NewRequest>,
+    /// [ request id : (client id, request event id, response event id) ]
+    pending_requests: HashMap<u64, (ClientId, u16, u16)>,
+    /// [ request id : deadline ]
+    request_deadlines: HashMap<u64, Instant>,
 }
 
 impl<E: EventPack> EventServerCore<E>
@@ -30,10 +57,18 @@ impl<E: EventPack> EventServerCore<E>
     /// Makes a new event server core.
     pub(crate) fn new(server: Server<EventWrapper<E>>) -> Self
     {
+        let (request_sender, request_receiver) = crossbeam::channel::unbounded();
         Self {
             inner: server,
             counter: 0u32,
             pending_connect: HashMap::default(),
+            schema_policy: SchemaPolicy::default(),
+            unverified_clients: HashMap::default(),
+            request_counter: AtomicU64::new(0u64),
+            request_sender,
+            request_receiver,
+            pending_requests: HashMap::default(),
+            request_deadlines: HashMap::default(),
         }
     }
 
@@ -42,9 +77,7 @@ impl<E: EventPack> EventServerCore<E>
     /// Returns `None` if the client is not connected.
     pub fn pending_connect(&self, client_id: ClientId) -> Option<u32>
     {
-        let Some(entry) = self.pending_connect.get(&client_id) else {
-            return None;
-        };
+        let entry = self.pending_connect.get(&client_id)?;
         let counter = entry.load(Ordering::Relaxed);
         if counter > u32::MAX as u64 {
             return None;
@@ -62,9 +95,9 @@ impl<E: EventPack> EventServerCore<E>
             None => u64::MAX,
         };
 
-        self.pending_connect
-            .get(&client_id)
-            .map(|c| c.store(new_val, Ordering::Relaxed));
+        if let Some(c) = self.pending_connect.get(&client_id) {
+            c.store(new_val, Ordering::Relaxed);
+        }
     }
 
     /// Clears the pending connect counter for a client if the input counter equals it.
@@ -80,26 +113,170 @@ impl<E: EventPack> EventServerCore<E>
         }
     }
 
-    /// Sends a message to a client.
-    pub(crate) fn send<T: SimplenetEvent>(&self, registry: &EventRegistry<E>, client_id: ClientId, message: T)
+    /// Sets the schema mismatch policy.
+    pub(crate) fn set_schema_policy(&mut self, policy: SchemaPolicy)
     {
-        if self.pending_connect(client_id).is_some() {
-            tracing::warn!(client_id, "dropping message because there is a pending connect event");
+        self.schema_policy = policy;
+    }
+
+    /// Accesses the schema mismatch policy.
+    pub(crate) fn schema_policy(&self) -> SchemaPolicy
+    {
+        self.schema_policy
+    }
+
+    /// Holds a client's connect report until its event schema is verified.
+    pub(crate) fn hold_connect(&mut self, client_id: ClientId, counter: u32, report: ServerReport<E::ConnectMsg>)
+    {
+        self.unverified_clients
+            .insert(client_id, Some((counter, report)));
+    }
+
+    /// Marks a client's event schema as verified.
+    ///
+    /// Returns the client's held connect report, if there is one.
+    pub(crate) fn verify_client(&mut self, client_id: ClientId) -> Option<HeldConnect<E>>
+    {
+        let Some(Some(_)) = self.unverified_clients.get(&client_id) else {
+            return None;
+        };
+        self.unverified_clients.remove(&client_id).flatten()
+    }
+
+    /// Discards a client's held connect report because its event schema didn't match.
+    ///
+    /// Does nothing if the client's connect report isn't held.
+    pub(crate) fn reject_unverified_client(&mut self, client_id: ClientId)
+    {
+        if let Some(entry) = self.unverified_clients.get_mut(&client_id) {
+            *entry = None;
+        }
+    }
+
+    /// Checks if a client's connect report is held or was discarded.
+    ///
+    /// Events from these clients are not exposed to users.
+    pub(crate) fn is_unverified_client(&self, client_id: ClientId) -> bool
+    {
+        self.unverified_clients.contains_key(&client_id)
+    }
+
+    /// Forgets a disconnected client's unverified status.
+    ///
+    /// Returns `true` if users never saw the client's connect report.
+    pub(crate) fn remove_unverified_client(&mut self, client_id: ClientId) -> bool
+    {
+        self.unverified_clients.remove(&client_id).is_some()
+    }
+
+    /// Sends the server's event schema to a client.
+    ///
+    /// This bypasses the pending-connect check because it is sent when a client connects.
+    pub(crate) fn send_schema(&self, registry: &EventRegistry<E>, client_id: ClientId)
+    {
+        let Ok(data) = BincodeCodec::encode(registry.schema()) else {
+            tracing::error!("failed serializing server event schema");
             return;
         };
 
+        self.inner
+            .send(client_id, InternalEvent { id: SCHEMA_EVENT_ID, data })
+    }
+
+    /// Checks if events can be sent to a client.
+    fn check_sendable(&self, client_id: ClientId) -> Result<(), SendError>
+    {
+        if !self.pending_connect.contains_key(&client_id) {
+            tracing::debug!(client_id, "dropping event sent to unknown client");
+            return Err(SendError::UnknownClient);
+        }
+        if self.pending_connect(client_id).is_some() {
+            tracing::warn!(client_id, "dropping event because there is a pending connect event");
+            return Err(SendError::PendingConnect);
+        };
+
+        Ok(())
+    }
+
+    /// Serializes a message for sending to clients.
+    fn encode_message<T: SimplenetEvent>(
+        &self,
+        registry: &EventRegistry<E>,
+        message: &T,
+    ) -> Result<InternalEvent, SendError>
+    {
         let Some(message_event_id) = registry.get_message_id::<T>() else {
             tracing::error!("server message type is not registered");
-            return;
+            return Err(SendError::UnregisteredType);
         };
 
-        let Ok(data) = bincode::DefaultOptions::new().serialize(&message) else {
+        let data = registry.encode(message).map_err(|err| {
             tracing::error!("failed serializing server message");
-            return;
-        };
+            SendError::Encode(err)
+        })?;
 
-        self.inner
-            .send(client_id, InternalEvent { id: message_event_id, data })
+        Ok(InternalEvent { id: message_event_id, data })
+    }
+
+    /// Sends a serialized message to a client.
+    fn send_encoded(&self, client_id: ClientId, message: InternalEvent) -> Result<(), SendError>
+    {
+        self.check_sendable(client_id)?;
+        self.inner.send(client_id, message);
+
+        Ok(())
+    }
+
+    /// Sends a message to a client.
+    pub(crate) fn send<T: SimplenetEvent>(
+        &self,
+        registry: &EventRegistry<E>,
+        client_id: ClientId,
+        message: T,
+    ) -> Result<(), SendError>
+    {
+        let message = self.encode_message(registry, &message)?;
+        self.send_encoded(client_id, message)
+    }
+
+    /// Sends a message to multiple clients.
+    ///
+    /// The message is only serialized once. Clients that can't receive the message are skipped and returned in
+    /// the error.
+    pub(crate) fn send_many<T: SimplenetEvent>(
+        &self,
+        registry: &EventRegistry<E>,
+        client_ids: impl IntoIterator<Item = ClientId>,
+        message: T,
+    ) -> Result<(), SendManyError>
+    {
+        let message = self
+            .encode_message(registry, &message)
+            .map_err(SendManyError::Message)?;
+
+        let failures: Vec<(ClientId, SendError)> = client_ids
+            .into_iter()
+            .filter_map(|client_id| {
+                self.send_encoded(client_id, message.clone())
+                    .err()
+                    .map(|err| (client_id, err))
+            })
+            .collect();
+
+        if !failures.is_empty() {
+            return Err(SendManyError::Clients(failures));
+        }
+
+        Ok(())
+    }
+
+    /// Iterates connected clients whose connect reports have been read.
+    pub(crate) fn connected_clients(&self) -> impl Iterator<Item = ClientId> + '_
+    {
+        self.pending_connect
+            .keys()
+            .copied()
+            .filter(|client_id| self.pending_connect(*client_id).is_none())
     }
 
     /// Sends a response to a client.
@@ -108,38 +285,208 @@ impl<E: EventPack> EventServerCore<E>
         registry: &EventRegistry<E>,
         token: RequestToken,
         response: Resp,
-    )
+    ) -> Result<(), SendError>
     {
-        let client_id = token.client_id();
-        if self.pending_connect(client_id).is_some() {
-            tracing::warn!(client_id, "dropping response because there is a pending connect event");
-            return;
-        };
+        self.check_sendable(token.client_id())?;
 
         let Some(response_event_id) = registry.get_response_id::<Resp>() else {
             tracing::error!("server response type is not registered");
-            return;
+            return Err(SendError::UnregisteredType);
         };
 
-        let Ok(data) = bincode::DefaultOptions::new().serialize(&response) else {
+        let data = registry.encode(&response).map_err(|err| {
             tracing::error!("failed serializing server response");
-            return;
-        };
+            SendError::Encode(err)
+        })?;
 
         self.inner
-            .respond(token, InternalEvent { id: response_event_id, data })
+            .respond(token, InternalEvent { id: response_event_id, data });
+
+        Ok(())
     }
 
-    /// Sends an ack to a client.
-    pub(crate) fn ack(&self, token: RequestToken)
+    /// Fails a client's stream request.
+    pub(crate) fn fail_stream(&self, token: RequestToken) -> Result<(), SendError>
+    {
+        self.check_sendable(token.client_id())?;
+
+        self.inner
+            .respond(token, InternalEvent { id: STREAM_ERROR_EVENT_ID, data: Vec::default() });
+
+        Ok(())
+    }
+
+    /// Sends a stream chunk to a client.
+    ///
+    /// Chunks for requests from dead sessions are silently dropped.
+    pub(crate) fn send_chunk<Chunk: SimplenetEvent>(
+        &self,
+        registry: &EventRegistry<E>,
+        token: &RequestToken,
+        chunk: Chunk,
+    ) -> Result<(), SendError>
     {
         let client_id = token.client_id();
-        if self.pending_connect(client_id).is_some() {
-            tracing::warn!(client_id, "dropping response because there is a pending connect event");
-            return;
+        self.check_sendable(client_id)?;
+
+        let Some(chunk_event_id) = registry.get_stream_chunk_id::<Chunk>() else {
+            tracing::error!("server stream chunk type is not registered");
+            return Err(SendError::UnregisteredType);
         };
 
-        self.inner.ack(token)
+        let payload = registry.encode(&chunk).map_err(|err| {
+            tracing::error!("failed serializing server stream chunk");
+            SendError::Encode(err)
+        })?;
+
+        // requests cannot leak across sessions
+        if token.destination_is_dead() {
+            let request_id = token.request_id();
+            tracing::debug!(client_id, request_id, "tried to send stream chunk to dead session");
+            return Err(SendError::StaleRequest);
+        }
+
+        let data = encode_request_id_frame(token.request_id(), &payload);
+        self.inner
+            .send(client_id, InternalEvent { id: chunk_event_id, data });
+
+        Ok(())
+    }
+
+    /// Sends a request to a client.
+    ///
+    /// Returns the request id. If `timeout` is `None`, then the request type's default timeout is used (if any).
+    pub(crate) fn request<Req: SimplenetEvent>(
+        &self,
+        registry: &EventRegistry<E>,
+        client_id: ClientId,
+        request: Req,
+        timeout: Option<Duration>,
+    ) -> Result<u64, SendError>
+    {
+        self.check_sendable(client_id)?;
+
+        let Some((request_event_id, response_event_id)) = registry.get_server_request_ids::<Req>() else {
+            tracing::error!("server request type is not registered");
+            return Err(SendError::UnregisteredType);
+        };
+
+        let payload = registry.encode(&request).map_err(|err| {
+            tracing::error!("failed serializing server request");
+            SendError::Encode(err)
+        })?;
+
+        let request_id = self.request_counter.fetch_add(1, Ordering::Relaxed);
+        let data = encode_request_id_frame(request_id, &payload);
+        self.inner
+            .send(client_id, InternalEvent { id: request_event_id, data });
+
+        // use channel since we are immutable
+        let deadline = timeout
+            .or_else(|| registry.get_server_request_timeout(request_event_id))
+            .map(|timeout| Instant::now() + timeout);
+        if self
+            .request_sender
+            .send((request_id, (client_id, request_event_id, response_event_id), deadline))
+            .is_err()
+        {
+            tracing::error!("request tracker channel is broken");
+        }
+
+        Ok(request_id)
+    }
+
+    /// Drains pending request-tracker entries now that we are mutable.
+    fn sync_pending_requests(&mut self)
+    {
+        while let Ok((request_id, entry, deadline)) = self.request_receiver.try_recv() {
+            self.pending_requests.insert(request_id, entry);
+            if let Some(deadline) = deadline {
+                self.request_deadlines.insert(request_id, deadline);
+            }
+        }
+    }
+
+    /// Removes a server request from the request tracker.
+    ///
+    /// Returns `None` if the request isn't tracked or was sent to a different client.
+    pub(crate) fn remove_request(&mut self, client_id: ClientId, request_id: u64) -> Option<(u16, u16)>
+    {
+        self.sync_pending_requests();
+
+        let (target, request_event_id, response_event_id) = self.pending_requests.get(&request_id).copied()?;
+        if target != client_id {
+            return None;
+        }
+        self.pending_requests.remove(&request_id);
+        self.request_deadlines.remove(&request_id);
+
+        Some((request_event_id, response_event_id))
+    }
+
+    /// Removes all of a client's server requests from the request tracker.
+    ///
+    /// Returns `(request id, request event id, response event id)` for each request.
+    pub(crate) fn remove_client_requests(&mut self, client_id: ClientId) -> Vec<(u64, u16, u16)>
+    {
+        self.sync_pending_requests();
+
+        let mut removed = Vec::default();
+        self.pending_requests
+            .retain(|request_id, (target, request_event_id, response_event_id)| {
+                if *target != client_id {
+                    return true;
+                }
+                removed.push((*request_id, *request_event_id, *response_event_id));
+                false
+            });
+        removed.sort_unstable();
+        for (request_id, _, _) in removed.iter() {
+            self.request_deadlines.remove(request_id);
+        }
+
+        removed
+    }
+
+    /// Removes server requests whose deadlines have elapsed from the request tracker.
+    ///
+    /// Returns `(client id, request id, request event id, response event id)` for each expired request. Late
+    /// responses to these requests are ignored.
+    pub(crate) fn expire_requests(&mut self) -> Vec<(ClientId, u64, u16, u16)>
+    {
+        self.sync_pending_requests();
+        if self.request_deadlines.is_empty() {
+            return Vec::default();
+        }
+
+        let now = Instant::now();
+        let mut expired = Vec::default();
+        self.request_deadlines.retain(|request_id, deadline| {
+            if *deadline > now {
+                return true;
+            }
+            expired.push(*request_id);
+            false
+        });
+        expired.sort_unstable();
+
+        expired
+            .into_iter()
+            .filter_map(|request_id| {
+                let (client_id, request_event_id, response_event_id) =
+                    self.pending_requests.remove(&request_id)?;
+                Some((client_id, request_id, request_event_id, response_event_id))
+            })
+            .collect()
+    }
+
+    /// Sends an ack to a client.
+    pub(crate) fn ack(&self, token: RequestToken) -> Result<(), SendError>
+    {
+        self.check_sendable(token.client_id())?;
+        self.inner.ack(token);
+
+        Ok(())
     }
 
     /// Sends a request rejection to a client.
@@ -157,9 +504,7 @@ impl<E: EventPack> EventServerCore<E>
     /// Extracts the next server event.
     pub(crate) fn next(&mut self) -> Option<(u32, ClientId, ServerEventFrom<EventWrapper<E>>)>
     {
-        let Some((client_id, next)) = self.inner.next() else {
-            return None;
-        };
+        let (client_id, next) = self.inner.next()?;
         self.counter += 1;
 
         match &next {
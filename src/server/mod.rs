@@ -1,15 +1,27 @@
 //module tree
 mod app_ext;
+mod client_entities;
+mod client_response;
+mod event_bridge;
 mod event_queue_connector;
 mod event_queues;
 mod event_server;
 mod event_server_core;
+mod pending_requests;
 mod readers;
+mod rooms;
+mod triggers;
 
 //API exports
 pub use crate::server::app_ext::*;
+pub use crate::server::client_entities::*;
+pub use crate::server::client_response::*;
+pub use crate::server::event_bridge::*;
 pub(crate) use crate::server::event_queue_connector::*;
 pub(crate) use crate::server::event_queues::*;
 pub use crate::server::event_server::*;
 pub(crate) use crate::server::event_server_core::*;
+pub use crate::server::pending_requests::*;
 pub use crate::server::readers::*;
+pub use crate::server::rooms::*;
+pub use crate::server::triggers::*;
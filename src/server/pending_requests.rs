@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+use bevy_ecs::prelude::*;
+use bevy_simplenet::{ClientId, RequestToken};
+
+use crate::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Server resource that holds client request tokens until they are answered or their deadlines expire.
+///
+/// Use this when a request is answered in a later tick. Tokens whose deadlines expire are rejected with
+/// [`EventServer::reject`] in [`RefreshSet`], and a client's tokens are discarded when the client disconnects.
+///
+/// See [`SimplenetServerEventAppExt::enable_simplenet_pending_requests`].
+#[derive(Resource)]
+pub struct ServerPendingRequests<E: EventPack>
+{
+    /// [ (client id, request id) : (token, deadline) ]
+    requests: HashMap<(ClientId, u64), (RequestToken, Instant)>,
+    /// [ client id : number of pending requests ]
+    counts: HashMap<ClientId, usize>,
+    phantom: PhantomData<E>,
+}
+
+impl<E: EventPack> ServerPendingRequests<E>
+{
+    /// Holds a request token until `timeout` elapses.
+    ///
+    /// See [`Self::park_until`].
+    pub fn park(&mut self, token: RequestToken, timeout: Duration)
+    {
+        self.park_until(token, Instant::now() + timeout);
+    }
+
+    /// Holds a request token until the deadline.
+    ///
+    /// If a token for the same request is already parked, it is replaced and the old token is rejected.
+    pub fn park_until(&mut self, token: RequestToken, deadline: Instant)
+    {
+        let client_id = token.client_id();
+        let request_id = token.request_id();
+        if self
+            .requests
+            .insert((client_id, request_id), (token, deadline))
+            .is_some()
+        {
+            tracing::warn!(client_id, request_id, "replacing pending request token");
+            return;
+        }
+
+        *self.counts.entry(client_id).or_default() += 1;
+    }
+
+    /// Removes a request token so it can be answered.
+    pub fn take(&mut self, client_id: ClientId, request_id: u64) -> Option<RequestToken>
+    {
+        let (token, _) = self.requests.remove(&(client_id, request_id))?;
+        self.decrement_count(client_id);
+        Some(token)
+    }
+
+    /// Checks if a request token is parked.
+    pub fn contains(&self, client_id: ClientId, request_id: u64) -> bool
+    {
+        self.requests.contains_key(&(client_id, request_id))
+    }
+
+    /// Gets the deadline of a parked request token.
+    pub fn deadline(&self, client_id: ClientId, request_id: u64) -> Option<Instant>
+    {
+        self.requests
+            .get(&(client_id, request_id))
+            .map(|(_, deadline)| *deadline)
+    }
+
+    /// Gets the number of parked request tokens for a client.
+    pub fn num_pending(&self, client_id: ClientId) -> usize
+    {
+        self.counts.get(&client_id).copied().unwrap_or_default()
+    }
+
+    /// Iterates the number of parked request tokens for each client with at least one parked token.
+    pub fn iter_counts(&self) -> impl Iterator<Item = (ClientId, usize)> + '_
+    {
+        self.counts
+            .iter()
+            .map(|(client_id, count)| (*client_id, *count))
+    }
+
+    /// Gets the total number of parked request tokens.
+    pub fn len(&self) -> usize
+    {
+        self.requests.len()
+    }
+
+    /// Checks if there are no parked request tokens.
+    pub fn is_empty(&self) -> bool
+    {
+        self.requests.is_empty()
+    }
+
+    fn decrement_count(&mut self, client_id: ClientId)
+    {
+        let Some(count) = self.counts.get_mut(&client_id) else {
+            return;
+        };
+        *count -= 1;
+        if *count == 0 {
+            self.counts.remove(&client_id);
+        }
+    }
+}
+
+impl<E: EventPack> Default for ServerPendingRequests<E>
+{
+    fn default() -> Self
+    {
+        Self {
+            requests: HashMap::default(),
+            counts: HashMap::default(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Rejects parked request tokens whose deadlines have expired.
+///
+/// Does nothing if pending requests are not enabled.
+pub(crate) fn reject_expired_requests<E: EventPack>(world: &mut World, server: &EventServerCore<E>)
+{
+    let Some(mut pending) = world.get_resource_mut::<ServerPendingRequests<E>>() else {
+        return;
+    };
+    if pending.is_empty() {
+        return;
+    }
+
+    let now = Instant::now();
+    let expired: Vec<(ClientId, u64)> = pending
+        .requests
+        .iter()
+        .filter(|(_, (_, deadline))| *deadline <= now)
+        .map(|(key, _)| *key)
+        .collect();
+
+    for (client_id, request_id) in expired {
+        let Some(token) = pending.take(client_id, request_id) else {
+            continue;
+        };
+        tracing::debug!(client_id, request_id, "rejecting expired pending request");
+        server.reject(token);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Discards a client's parked request tokens after it disconnects.
+///
+/// Does nothing if pending requests are not enabled.
+pub(crate) fn remove_client_pending_requests<E: EventPack>(world: &mut World, client_id: ClientId)
+{
+    let Some(mut pending) = world.get_resource_mut::<ServerPendingRequests<E>>() else {
+        return;
+    };
+    if pending.counts.remove(&client_id).is_none() {
+        return;
+    }
+
+    pending
+        .requests
+        .retain(|(request_client_id, _), _| *request_client_id != client_id);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
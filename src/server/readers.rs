@@ -24,6 +24,66 @@ impl<'w, E: EventPack> ServerConnectionReader<'w, E>
             (*id, report)
         })
     }
+
+    /// Iterates clients whose event schemas don't match the server's.
+    ///
+    /// Schemas are exchanged when a client connects, so with [`SchemaPolicy::Report`] a mismatch will be reported
+    /// shortly after the client's `ServerReport::Connected`. With [`SchemaPolicy::Disconnect`] the mismatch is
+    /// reported instead of the client's `ServerReport::Connected`.
+    pub fn schema_mismatches(&self) -> impl Iterator<Item = (ClientId, &SchemaMismatch)> + '_
+    {
+        self.events
+            .iter_schema_mismatches()
+            .map(|(id, mismatch)| (*id, mismatch))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Server reader for client messages and requests that failed to deserialize.
+///
+/// Requests that fail to deserialize are rejected automatically.
+#[derive(SystemParam)]
+pub struct ServerDecodeErrorReader<'w, E: EventPack>
+{
+    events: Res<'w, ServerDecodeErrorQueue<E>>,
+}
+
+impl<'w, E: EventPack> ServerDecodeErrorReader<'w, E>
+{
+    /// Iterates the available decode errors.
+    pub fn iter(&self) -> impl Iterator<Item = (ClientId, &DecodeError)> + '_
+    {
+        self.events.iter().map(|(id, error)| (*id, error))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Server reader for client request cancellations.
+///
+/// Cancelled requests can still be responded to, but the client will discard the response.
+#[derive(SystemParam)]
+pub struct ServerCancelReader<'w, E: EventPack>
+{
+    events: Res<'w, ServerCancelQueue<E>>,
+}
+
+impl<'w, E: EventPack> ServerCancelReader<'w, E>
+{
+    /// Iterates the available cancellations as `(client id, request id)`.
+    pub fn iter(&self) -> impl Iterator<Item = (ClientId, u64)> + '_
+    {
+        self.events.iter().copied()
+    }
+
+    /// Checks if the request for a token was cancelled this tick.
+    pub fn is_cancelled(&self, token: &RequestToken) -> bool
+    {
+        self.events
+            .iter()
+            .any(|(client_id, request_id)| *client_id == token.client_id() && *request_id == token.request_id())
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -46,6 +106,41 @@ impl<'w, E: EventPack, T: SimplenetEvent> ServerMessageReader<'w, E, T>
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Server reader for client messages that tracks which messages each system has read.
+///
+/// [`ServerMessageReader`] only sees messages received in the current tick. This reader works like
+/// `bevy_ecs::event::EventReader` instead: each system sees each message exactly once, even if it runs in a
+/// schedule that skips ticks or runs several times per tick (e.g. `FixedUpdate`).
+///
+/// Messages are buffered for two ticks (or two fixed-update cycles if `bevy_time` is driving fixed updates), so a
+/// system that doesn't run for longer than that will miss messages. Messages from a client's previous session are
+/// discarded when it disconnects.
+#[derive(SystemParam)]
+pub struct ServerMessageCursor<'w, 's, E: EventPack, T: SimplenetEvent>
+{
+    cursor: Local<'s, usize>,
+    events: Res<'w, ServerMessageQueue<E, T>>,
+}
+
+impl<'w, 's, E: EventPack, T: SimplenetEvent> ServerMessageCursor<'w, 's, E, T>
+{
+    /// Iterates client messages this system hasn't read yet.
+    pub fn read(&mut self) -> impl Iterator<Item = (ClientId, &T)> + '_
+    {
+        self.events
+            .read(&mut self.cursor)
+            .map(|(id, message)| (*id, message))
+    }
+
+    /// Marks all buffered messages as read.
+    pub fn clear(&mut self)
+    {
+        self.read().for_each(|_| ());
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Server source for client requests.
 ///
 /// Requests can only be drained here, since we expect the user to do something with request tokens.
@@ -65,3 +160,21 @@ impl<'w, E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent> ServerRequestS
 }
 
 //-------------------------------------------------------------------------------------------------------------------
+
+/// Server reader for client responses to server requests.
+#[derive(SystemParam)]
+pub struct ServerResponseReader<'w, E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent>
+{
+    events: Res<'w, ServerResponseQueue<E, Req, Resp>>,
+}
+
+impl<'w, E: EventPack, Req: SimplenetEvent, Resp: SimplenetEvent> ServerResponseReader<'w, E, Req, Resp>
+{
+    /// Iterates the available client responses.
+    pub fn iter(&self) -> impl Iterator<Item = (ClientId, &ClientResponse<Resp>)> + '_
+    {
+        self.events.iter().map(|(id, response)| (*id, response))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,172 @@
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+
+use bevy_ecs::prelude::*;
+use bevy_simplenet::ClientId;
+
+use crate::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Identifies a room in [`ServerRooms`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct RoomId(u64);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Server resource that groups clients into rooms for message routing.
+///
+/// Clients are removed from all rooms when their `ServerReport::Disconnected` is drained in [`RefreshSet`]. Only
+/// connected clients should join rooms, otherwise they will stay members until they connect and disconnect again.
+///
+/// Use [`EventServer::send_room`] to send a message to all members of a room.
+#[derive(Resource)]
+pub struct ServerRooms<E: EventPack>
+{
+    /// Room id counter.
+    next_id: u64,
+    /// [ room id : members ]
+    rooms: HashMap<RoomId, HashSet<ClientId>>,
+    /// [ client id : rooms ]
+    memberships: HashMap<ClientId, HashSet<RoomId>>,
+    phantom: PhantomData<E>,
+}
+
+impl<E: EventPack> ServerRooms<E>
+{
+    /// Makes a new empty room.
+    pub fn create_room(&mut self) -> RoomId
+    {
+        let room_id = RoomId(self.next_id);
+        self.next_id += 1;
+        self.rooms.insert(room_id, HashSet::default());
+        room_id
+    }
+
+    /// Removes a room and all its memberships.
+    ///
+    /// Returns `false` if the room doesn't exist.
+    pub fn remove_room(&mut self, room_id: RoomId) -> bool
+    {
+        let Some(members) = self.rooms.remove(&room_id) else {
+            return false;
+        };
+
+        for client_id in members {
+            self.remove_membership(client_id, room_id);
+        }
+
+        true
+    }
+
+    /// Adds a client to a room.
+    ///
+    /// Returns `false` if the room doesn't exist or the client is already a member.
+    pub fn join(&mut self, room_id: RoomId, client_id: ClientId) -> bool
+    {
+        let Some(members) = self.rooms.get_mut(&room_id) else {
+            return false;
+        };
+        if !members.insert(client_id) {
+            return false;
+        }
+
+        self.memberships
+            .entry(client_id)
+            .or_default()
+            .insert(room_id);
+
+        true
+    }
+
+    /// Removes a client from a room.
+    ///
+    /// Returns `false` if the room doesn't exist or the client is not a member.
+    pub fn leave(&mut self, room_id: RoomId, client_id: ClientId) -> bool
+    {
+        let Some(members) = self.rooms.get_mut(&room_id) else {
+            return false;
+        };
+        if !members.remove(&client_id) {
+            return false;
+        }
+
+        self.remove_membership(client_id, room_id);
+
+        true
+    }
+
+    /// Returns `true` if the room exists.
+    pub fn has_room(&self, room_id: RoomId) -> bool
+    {
+        self.rooms.contains_key(&room_id)
+    }
+
+    /// Returns `true` if the client is a member of the room.
+    pub fn is_member(&self, room_id: RoomId, client_id: ClientId) -> bool
+    {
+        self.rooms
+            .get(&room_id)
+            .is_some_and(|members| members.contains(&client_id))
+    }
+
+    /// Iterates the members of a room.
+    ///
+    /// The iterator is empty if the room doesn't exist.
+    pub fn members(&self, room_id: RoomId) -> impl Iterator<Item = ClientId> + '_
+    {
+        self.rooms
+            .get(&room_id)
+            .into_iter()
+            .flat_map(|members| members.iter().copied())
+    }
+
+    /// Iterates the rooms a client is a member of.
+    pub fn rooms_of(&self, client_id: ClientId) -> impl Iterator<Item = RoomId> + '_
+    {
+        self.memberships
+            .get(&client_id)
+            .into_iter()
+            .flat_map(|rooms| rooms.iter().copied())
+    }
+
+    /// Removes a client from all rooms.
+    pub(crate) fn remove_client(&mut self, client_id: ClientId)
+    {
+        let Some(rooms) = self.memberships.remove(&client_id) else {
+            return;
+        };
+
+        for room_id in rooms {
+            if let Some(members) = self.rooms.get_mut(&room_id) {
+                members.remove(&client_id);
+            }
+        }
+    }
+
+    fn remove_membership(&mut self, client_id: ClientId, room_id: RoomId)
+    {
+        let Some(rooms) = self.memberships.get_mut(&client_id) else {
+            return;
+        };
+        rooms.remove(&room_id);
+        if rooms.is_empty() {
+            self.memberships.remove(&client_id);
+        }
+    }
+}
+
+impl<E: EventPack> Default for ServerRooms<E>
+{
+    fn default() -> Self
+    {
+        Self {
+            next_id: 0u64,
+            rooms: HashMap::default(),
+            memberships: HashMap::default(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
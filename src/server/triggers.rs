@@ -0,0 +1,28 @@
+use bevy_ecs::prelude::*;
+use bevy_simplenet::ClientId;
+
+use crate::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Observer event triggered on the server for each client message of type `T`.
+///
+/// Enable with [`SimplenetServerEventAppExt::add_simplenet_server_trigger`].
+/**
+```rust,ignore
+app.add_simplenet_server_trigger::<MyChannel, Chat>()
+    .add_observer(|trigger: Trigger<NetMsg<Chat>>| {
+        println!("{}: {:?}", trigger.client_id, trigger.message);
+    });
+```
+*/
+#[derive(Event, Debug, Clone)]
+pub struct NetMsg<T: SimplenetEvent>
+{
+    /// The client that sent the message.
+    pub client_id: ClientId,
+    /// The message.
+    pub message: T,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,2 @@
+//module tree
+mod tests;
@@ -0,0 +1,269 @@
+use bevy_app::*;
+use bevy_simplenet_events::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// These tests don't need a client or server, so they can be run in every feature configuration:
+// - cargo test --test registry --no-default-features --features client
+// - cargo test --test registry --no-default-features --features server
+// - cargo test --test registry
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(SimplenetEvent, Serialize, Deserialize)]
+struct DemoMsg1(usize);
+
+#[derive(SimplenetEvent, Serialize, Deserialize)]
+struct DemoMsg2(usize);
+
+#[derive(SimplenetEvent, Serialize, Deserialize)]
+struct DemoMsg3(usize);
+
+#[derive(SimplenetEvent, Serialize, Deserialize)]
+struct DemoRequest1(usize);
+
+#[derive(SimplenetEvent, Serialize, Deserialize)]
+struct DemoRequest2(usize);
+
+#[derive(SimplenetEvent, Serialize, Deserialize)]
+struct DemoResponse1(usize);
+
+#[derive(SimplenetEvent, Serialize, Deserialize)]
+#[simplenet(id = "demo.named1")]
+struct DemoNamed1(usize);
+
+#[derive(SimplenetEvent, Serialize, Deserialize)]
+#[simplenet(stable_id)]
+struct DemoNamed2(usize);
+
+#[derive(SimplenetEvent, Serialize, Deserialize)]
+#[simplenet(id = "demo.named1")]
+struct DemoNamedDuplicate(usize);
+
+#[derive(SimplenetEvent, Serialize, Deserialize)]
+#[simplenet(id = 42)]
+struct DemoPinned(usize);
+
+#[derive(SimplenetEvent, Serialize, Deserialize)]
+#[simplenet(id = 42)]
+struct DemoPinnedDuplicate(usize);
+
+struct DemoJsonCodec;
+impl EventCodec for DemoJsonCodec
+{
+    fn encode<T: Serialize>(event: &T) -> Result<Vec<u8>, CodecError>
+    {
+        serde_json::to_vec(event).map_err(CodecError::new)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CodecError>
+    {
+        serde_json::from_slice(bytes).map_err(CodecError::new)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct DemoConnectMsg;
+
+#[derive(Debug, Clone)]
+struct DemoChannel;
+impl EventPack for DemoChannel
+{
+    type ConnectMsg = DemoConnectMsg;
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn setup_event_app(app: &mut App)
+{
+    app.register_simplenet_server_message::<DemoChannel, DemoMsg3>()
+        .register_simplenet_client_message::<DemoChannel, DemoMsg1>()
+        .register_simplenet_client_message::<DemoChannel, DemoMsg2>()
+        .register_simplenet_server_message::<DemoChannel, DemoMsg1>()
+        .register_simplenet_request_response::<DemoChannel, DemoRequest1, DemoResponse1>()
+        .register_simplenet_request_response::<DemoChannel, DemoRequest2, ()>();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn schema_ids(app: &App) -> Vec<(u16, EventKind, &str)>
+{
+    app.simplenet_event_schema::<DemoChannel>()
+        .unwrap()
+        .iter()
+        .map(|entry| (entry.id, entry.kind, entry.type_name.as_str()))
+        .collect()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// event ids do not depend on which of the client/server features are enabled
+#[test]
+fn event_ids_independent_of_features()
+{
+    let mut app = App::new();
+    setup_event_app(&mut app);
+
+    assert_eq!(
+        schema_ids(&app),
+        vec![
+            (1, EventKind::Message, std::any::type_name::<DemoMsg3>()),
+            (2, EventKind::Message, std::any::type_name::<DemoMsg1>()),
+            (3, EventKind::Message, std::any::type_name::<DemoMsg2>()),
+            (4, EventKind::Request, std::any::type_name::<DemoRequest1>()),
+            (5, EventKind::Response, std::any::type_name::<DemoResponse1>()),
+            (6, EventKind::Request, std::any::type_name::<DemoRequest2>()),
+            (7, EventKind::Response, std::any::type_name::<()>()),
+        ]
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// identical registration produces identical fingerprints
+#[test]
+fn schema_fingerprint_is_deterministic()
+{
+    let mut app1 = App::new();
+    let mut app2 = App::new();
+    setup_event_app(&mut app1);
+    setup_event_app(&mut app2);
+
+    let schema1 = app1.simplenet_event_schema::<DemoChannel>().unwrap();
+    let schema2 = app2.simplenet_event_schema::<DemoChannel>().unwrap();
+    assert_eq!(schema1.fingerprint(), schema2.fingerprint());
+
+    let mut app3 = App::new();
+    app3.register_simplenet_client_message::<DemoChannel, DemoMsg1>();
+    let schema3 = app3.simplenet_event_schema::<DemoChannel>().unwrap();
+    assert_ne!(schema1.fingerprint(), schema3.fingerprint());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// named events get the same id regardless of registration order
+#[test]
+fn named_event_ids_independent_of_order()
+{
+    let mut app1 = App::new();
+    app1.register_simplenet_client_message::<DemoChannel, DemoNamed1>()
+        .register_simplenet_server_message::<DemoChannel, DemoNamed2>()
+        .register_simplenet_request_response::<DemoChannel, DemoRequest1, DemoNamed1>();
+
+    let mut app2 = App::new();
+    app2.register_simplenet_request_response::<DemoChannel, DemoRequest1, DemoNamed1>()
+        .register_simplenet_server_message::<DemoChannel, DemoNamed2>()
+        .register_simplenet_client_message::<DemoChannel, DemoNamed1>();
+
+    assert_eq!(schema_ids(&app1), schema_ids(&app2));
+    assert_eq!(
+        app1.simplenet_event_schema::<DemoChannel>().unwrap().fingerprint(),
+        app2.simplenet_event_schema::<DemoChannel>().unwrap().fingerprint()
+    );
+
+    // named ids don't collide with sequential ids
+    let ids = schema_ids(&app1);
+    assert_eq!(ids.len(), 4);
+    assert!(ids.contains(&(1, EventKind::Request, std::any::type_name::<DemoRequest1>())));
+    assert!(ids
+        .iter()
+        .filter(|(_, _, name)| *name != std::any::type_name::<DemoRequest1>())
+        .all(|(id, _, _)| *id >= 0x8000));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// named events with the same name panic on registration
+#[test]
+#[should_panic(expected = "event names must be unique")]
+fn named_event_id_collision_panics()
+{
+    let mut app = App::new();
+    app.register_simplenet_client_message::<DemoChannel, DemoNamed1>()
+        .register_simplenet_client_message::<DemoChannel, DemoNamedDuplicate>();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// events with an explicit id get that id in the stable id space regardless of registration order
+#[test]
+fn explicit_event_ids_independent_of_order()
+{
+    let mut app1 = App::new();
+    app1.register_simplenet_client_message::<DemoChannel, DemoMsg1>()
+        .register_simplenet_client_message::<DemoChannel, DemoPinned>();
+
+    let mut app2 = App::new();
+    app2.register_simplenet_client_message::<DemoChannel, DemoPinned>()
+        .register_simplenet_client_message::<DemoChannel, DemoMsg1>();
+
+    assert_eq!(schema_ids(&app1), schema_ids(&app2));
+    let pinned = (0x8000 | 42, EventKind::Message, std::any::type_name::<DemoPinned>());
+    assert!(schema_ids(&app1).contains(&pinned));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// events with the same explicit id panic on registration
+#[test]
+#[should_panic(expected = "event names must be unique")]
+fn explicit_event_id_collision_panics()
+{
+    let mut app = App::new();
+    app.register_simplenet_client_message::<DemoChannel, DemoPinned>()
+        .register_simplenet_client_message::<DemoChannel, DemoPinnedDuplicate>();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// an event type can't be registered with two codecs
+#[test]
+#[should_panic(expected = "an event type can only have one codec")]
+fn conflicting_event_codecs_panic()
+{
+    let mut app = App::new();
+    app.register_simplenet_client_message_with_codec::<DemoChannel, DemoMsg1, DemoJsonCodec>()
+        .register_simplenet_server_message::<DemoChannel, DemoMsg1>();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// lz4 compresses large payloads and round-trips them
+#[test]
+fn lz4_codec_compresses_large_payloads()
+{
+    let snapshot = vec![7u64; 2048];
+    let raw = BincodeCodec::encode(&snapshot).unwrap();
+    let data = Lz4Codec::<BincodeCodec, 1024>::encode(&snapshot).unwrap();
+    assert!(data.len() < raw.len());
+    assert_eq!(Lz4Codec::<BincodeCodec, 1024>::decode::<Vec<u64>>(&data).unwrap(), snapshot);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// lz4 leaves payloads below the threshold uncompressed
+#[test]
+fn lz4_codec_skips_small_payloads()
+{
+    let snapshot = vec![7u64; 16];
+    let raw = BincodeCodec::encode(&snapshot).unwrap();
+    let data = Lz4Codec::<BincodeCodec, 1024>::encode(&snapshot).unwrap();
+    assert_eq!(data.len(), raw.len() + 1);
+    assert_eq!(&data[1..], &raw[..]);
+    assert_eq!(Lz4Codec::<BincodeCodec, 1024>::decode::<Vec<u64>>(&data).unwrap(), snapshot);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// lz4 rejects malformed payloads without allocating the claimed size
+#[test]
+fn lz4_codec_rejects_malformed_payloads()
+{
+    assert!(Lz4Codec::<BincodeCodec>::decode::<Vec<u64>>(&[]).is_err());
+    assert!(Lz4Codec::<BincodeCodec>::decode::<Vec<u64>>(&[9, 0]).is_err());
+    assert!(Lz4Codec::<BincodeCodec>::decode::<Vec<u64>>(&[1, 0xff, 0xff, 0xff, 0xff, 0]).is_err());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
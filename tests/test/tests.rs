@@ -1,10 +1,18 @@
 use bevy_app::*;
 use bevy_cobweb::prelude::*;
 use bevy_ecs::prelude::*;
-use bevy_simplenet::{ClientId, MessageStatus, RequestToken};
+use bevy_ecs::schedule::ScheduleLabel;
+use bevy_simplenet::{ClientId, MessageSignal, MessageStatus, RequestSignal, RequestToken};
 use bevy_simplenet_events::*;
+use bincode::Options;
 use enfync::AdoptOrDefault;
-use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct DemoTick;
 
 //-------------------------------------------------------------------------------------------------------------------
 
@@ -23,6 +31,38 @@ struct DemoRequest2(usize);
 #[derive(SimplenetEvent, Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 struct DemoResponse1(usize);
 
+#[derive(SimplenetEvent, Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+struct DemoRequest3(usize);
+
+#[derive(SimplenetEvent, Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+struct DemoServerRequest(usize);
+
+#[derive(SimplenetEvent, Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+struct DemoClientResponse(usize);
+
+#[derive(SimplenetEvent, Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+struct DemoStreamRequest(usize);
+
+#[derive(SimplenetEvent, Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+struct DemoStreamChunk(usize);
+
+/// Has a fixed wire id so tests can send it through a raw `bevy_simplenet` client.
+#[derive(SimplenetEvent, Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[simplenet(id = 7)]
+struct DemoPinnedRequest(usize);
+
+/// Always fails to deserialize.
+#[derive(SimplenetEvent, Serialize, Clone, Debug, Eq, PartialEq)]
+struct DemoUndecodable(usize);
+
+impl<'de> Deserialize<'de> for DemoUndecodable
+{
+    fn deserialize<D: Deserializer<'de>>(_: D) -> Result<Self, D::Error>
+    {
+        Err(serde::de::Error::custom("DemoUndecodable can't be deserialized"))
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 struct DemoConnectMsg(String);
 
@@ -33,6 +73,60 @@ impl EventPack for DemoChannel
     type ConnectMsg = DemoConnectMsg;
 }
 
+struct DemoJsonCodec;
+impl EventCodec for DemoJsonCodec
+{
+    fn encode<T: Serialize>(event: &T) -> Result<Vec<u8>, CodecError>
+    {
+        serde_json::to_vec(event).map_err(CodecError::new)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CodecError>
+    {
+        serde_json::from_slice(bytes).map_err(CodecError::new)
+    }
+}
+
+#[derive(SimplenetEvent, Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+struct DemoJsonMsg(String);
+
+#[derive(SimplenetEvent, Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+struct DemoSnapshot(Vec<u64>);
+
+static JSON_DECODE_COUNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+struct DemoCountingJsonCodec;
+impl EventCodec for DemoCountingJsonCodec
+{
+    fn encode<T: Serialize>(event: &T) -> Result<Vec<u8>, CodecError>
+    {
+        DemoJsonCodec::encode(event)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CodecError>
+    {
+        JSON_DECODE_COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        DemoJsonCodec::decode(bytes)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DemoJsonChannel;
+impl EventPack for DemoJsonChannel
+{
+    type ConnectMsg = DemoConnectMsg;
+
+    fn encode<T: Serialize>(event: &T) -> Result<Vec<u8>, CodecError>
+    {
+        DemoJsonCodec::encode(event)
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CodecError>
+    {
+        DemoJsonCodec::decode(bytes)
+    }
+}
+
 type DemoServerReport = bevy_simplenet::ServerReport<DemoConnectMsg>;
 
 fn demo_server_factory() -> bevy_simplenet::ServerFactory<EventWrapper<DemoChannel>>
@@ -48,6 +142,13 @@ fn demo_client_factory() -> bevy_simplenet::ClientFactory<EventWrapper<DemoChann
 //-------------------------------------------------------------------------------------------------------------------
 
 fn setup_server(app: &mut App) -> url::Url
+{
+    setup_server_in(app, First)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn setup_server_in(app: &mut App, schedule: impl ScheduleLabel) -> url::Url
 {
     tracing::info!("launching server...");
 
@@ -60,7 +161,7 @@ fn setup_server(app: &mut App) -> url::Url
     );
     let url = websocket_server.url();
 
-    app.insert_simplenet_server(websocket_server);
+    app.insert_simplenet_server_in(websocket_server, schedule);
 
     url
 }
@@ -117,6 +218,27 @@ fn num_message_events_server<T: SimplenetEvent>(reader: ServerMessageReader<Demo
 
 //-------------------------------------------------------------------------------------------------------------------
 
+fn read_message_cursor_server<T: SimplenetEvent>(mut cursor: ServerMessageCursor<DemoChannel, T>) -> usize
+{
+    cursor.read().count()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn read_message_cursor_server_late<T: SimplenetEvent>(mut cursor: ServerMessageCursor<DemoChannel, T>) -> usize
+{
+    cursor.read().count()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn read_message_cursor_client<T: SimplenetEvent>(mut cursor: ClientMessageCursor<DemoChannel, T>) -> usize
+{
+    cursor.read().count()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 fn num_message_events_client<T: SimplenetEvent>(reader: ClientMessageReader<DemoChannel, T>) -> usize
 {
     reader.iter().count()
@@ -207,19 +329,78 @@ fn check_client_received_response<Req: SimplenetEvent + Eq + PartialEq, Resp: Si
 
 //-------------------------------------------------------------------------------------------------------------------
 
+fn send_server_request<Req: SimplenetEvent>(
+    In((client_id, request)): In<(ClientId, Req)>,
+    server: EventServer<DemoChannel>,
+) -> Result<u64, SendError>
+{
+    server.request(client_id, request)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn get_client_requests<Req: SimplenetEvent, Resp: SimplenetEvent>(
+    mut source: ClientRequestSource<DemoChannel, Req, Resp>,
+) -> Vec<(ServerRequestToken, Req)>
+{
+    source.drain().collect()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn get_server_responses<Req: SimplenetEvent, Resp: SimplenetEvent + Clone>(
+    reader: ServerResponseReader<DemoChannel, Req, Resp>,
+) -> Vec<(ClientId, ClientResponse<Resp>)>
+{
+    reader
+        .iter()
+        .map(|(client_id, response)| {
+            let response = match response {
+                ClientResponse::Response(resp, id) => ClientResponse::Response(resp.clone(), *id),
+                ClientResponse::Ack(id) => ClientResponse::Ack(*id),
+                ClientResponse::Reject(id) => ClientResponse::Reject(*id),
+                ClientResponse::ResponseLost(id) => ClientResponse::ResponseLost(*id),
+                ClientResponse::Malformed(id) => ClientResponse::Malformed(*id),
+                ClientResponse::TimedOut(id) => ClientResponse::TimedOut(*id),
+            };
+            (client_id, response)
+        })
+        .collect()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn get_client_stream<Req: SimplenetEvent, Chunk: SimplenetEvent + Clone>(
+    reader: ClientStreamReader<DemoChannel, Req, Chunk>,
+) -> Vec<StreamResponse<Chunk>>
+{
+    reader
+        .iter()
+        .map(|response| match response {
+            StreamResponse::Chunk(chunk, id) => StreamResponse::Chunk(chunk.clone(), *id),
+            StreamResponse::Done(id) => StreamResponse::Done(*id),
+            StreamResponse::Error(id) => StreamResponse::Error(*id),
+            StreamResponse::SendFailed(id) => StreamResponse::SendFailed(*id),
+            StreamResponse::ResponseLost(id) => StreamResponse::ResponseLost(*id),
+            StreamResponse::Malformed(id) => StreamResponse::Malformed(*id),
+            StreamResponse::TimedOut(id) => StreamResponse::TimedOut(*id),
+            StreamResponse::Cancelled(id) => StreamResponse::Cancelled(*id),
+        })
+        .collect()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 fn check_client_connected_on_server(
     In(client_id): In<ClientId>,
     reader: ServerConnectionReader<DemoChannel>,
 ) -> bool
 {
     for (connected_id, connection) in reader.iter() {
-        match connection {
-            DemoServerReport::Connected(..) => {
-                if connected_id == client_id {
-                    return true;
-                }
+        if let DemoServerReport::Connected(..) = connection {
+            if connected_id == client_id {
+                return true;
             }
-            _ => (),
         }
     }
 
@@ -231,28 +412,92 @@ fn check_client_connected_on_server(
 fn check_client_connected_on_client(reader: ClientConnectionReader<DemoChannel>) -> bool
 {
     for connection in reader.iter() {
-        match connection {
-            bevy_simplenet::ClientReport::Connected => return true,
-            _ => (),
+        if let bevy_simplenet::ClientReport::Connected = connection {
+            return true;
         }
     }
 
-    return false;
+    false
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn check_client_closed_by_server(reader: ClientConnectionReader<DemoChannel>) -> bool
+{
+    reader
+        .iter()
+        .any(|connection| matches!(connection, bevy_simplenet::ClientReport::ClosedByServer(_)))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn get_schema_mismatches_server(reader: ServerConnectionReader<DemoChannel>) -> Vec<(ClientId, SchemaMismatch)>
+{
+    reader
+        .schema_mismatches()
+        .map(|(id, mismatch)| (id, mismatch.clone()))
+        .collect()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn get_schema_mismatches_client(reader: ClientConnectionReader<DemoChannel>) -> Vec<SchemaMismatch>
+{
+    reader.schema_mismatches().cloned().collect()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn get_client_diagnostics(reader: ClientDiagnosticReader<DemoChannel>) -> Vec<ClientDiagnostic>
+{
+    reader.iter().cloned().collect()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn get_decode_errors_server(reader: ServerDecodeErrorReader<DemoChannel>) -> Vec<(ClientId, u16, &'static str)>
+{
+    reader
+        .iter()
+        .map(|(id, err)| (id, err.event_id, err.type_name))
+        .collect()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn get_decode_errors_client(reader: ClientDecodeErrorReader<DemoChannel>) -> Vec<(u16, &'static str)>
+{
+    reader
+        .iter()
+        .map(|err| (err.event_id, err.type_name))
+        .collect()
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
 fn send_client_message<T: SimplenetEvent>(In(msg): In<T>, client: EventClient<DemoChannel>)
 {
-    client.send(msg);
+    client.send(msg).unwrap();
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
-fn try_send_client_message<T: SimplenetEvent>(In(msg): In<T>, client: EventClient<DemoChannel>) -> bool
+fn try_send_client_message<T: SimplenetEvent>(
+    In(msg): In<T>,
+    client: EventClient<DemoChannel>,
+) -> Result<MessageSignal, SendError>
+{
+    client.send(msg)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn try_send_client_request<Req: SimplenetEvent>(
+    In(request): In<Req>,
+    client: EventClient<DemoChannel>,
+) -> Result<RequestSignal, SendError>
 {
-    let signal = client.send(msg);
-    signal.status() != MessageStatus::Failed
+    client.request(request)
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -269,35 +514,87 @@ fn send_server_message<T: SimplenetEvent>(
     server: EventServer<DemoChannel>,
 )
 {
-    server.send(client_id, msg);
+    server.send(client_id, msg).unwrap();
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
-fn send_server_response<Req: SimplenetEvent, Resp: SimplenetEvent>(
-    In((token, response)): In<(RequestToken, Resp)>,
+fn try_send_server_message<T: SimplenetEvent>(
+    In((client_id, msg)): In<(ClientId, T)>,
+    server: EventServer<DemoChannel>,
+) -> Result<(), SendError>
+{
+    server.send(client_id, msg)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn send_many_server_message<T: SimplenetEvent>(
+    In((client_ids, msg)): In<(Vec<ClientId>, T)>,
+    server: EventServer<DemoChannel>,
+)
+{
+    server.send_many(client_ids, msg).unwrap();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn try_send_many_server_message<T: SimplenetEvent>(
+    In((client_ids, msg)): In<(Vec<ClientId>, T)>,
+    server: EventServer<DemoChannel>,
+) -> Result<(), SendManyError>
+{
+    server.send_many(client_ids, msg)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn broadcast_server_message<T: SimplenetEvent>(In(msg): In<T>, server: EventServer<DemoChannel>)
+{
+    server.broadcast(msg).unwrap();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn broadcast_except_server_message<T: SimplenetEvent>(
+    In((excluded, msg)): In<(ClientId, T)>,
     server: EventServer<DemoChannel>,
 )
 {
-    server.respond(token, response);
+    server.broadcast_except(excluded, msg).unwrap();
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
-fn send_server_ack<Req: SimplenetEvent, Resp: SimplenetEvent>(
-    In(token): In<RequestToken>,
+fn send_room_server_message<T: SimplenetEvent>(
+    In((room_id, msg)): In<(RoomId, T)>,
     server: EventServer<DemoChannel>,
+    rooms: Res<ServerRooms<DemoChannel>>,
 )
 {
-    server.ack(token);
+    server.send_room(&rooms, room_id, msg).unwrap();
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
-fn send_server_reject<Req: SimplenetEvent, Resp: SimplenetEvent>(
-    In(token): In<RequestToken>,
+fn send_server_response<Resp: SimplenetEvent>(
+    In((token, response)): In<(RequestToken, Resp)>,
     server: EventServer<DemoChannel>,
 )
+{
+    server.respond(token, response).unwrap();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn send_server_ack(In(token): In<RequestToken>, server: EventServer<DemoChannel>)
+{
+    server.ack(token).unwrap();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn send_server_reject(In(token): In<RequestToken>, server: EventServer<DemoChannel>)
 {
     server.reject(token);
 }
@@ -525,10 +822,9 @@ fn client_request()
     let request_id = token.request_id();
     assert_eq!(req, DemoRequest1(1));
 
-    server_app.world_mut().syscall(
-        (token, DemoResponse1(2)),
-        send_server_response::<DemoRequest1, DemoResponse1>,
-    );
+    server_app
+        .world_mut()
+        .syscall((token, DemoResponse1(2)), send_server_response::<DemoResponse1>);
 
     std::thread::sleep(std::time::Duration::from_millis(50));
 
@@ -606,12 +902,8 @@ fn client_request_acked_rejected()
     reqs.sort();
     assert_eq!(reqs, [DemoRequest2(2), DemoRequest2(22)]);
 
-    server_app
-        .world_mut()
-        .syscall(token1, send_server_ack::<DemoRequest2, ()>);
-    server_app
-        .world_mut()
-        .syscall(token2, send_server_reject::<DemoRequest2, ()>);
+    server_app.world_mut().syscall(token1, send_server_ack);
+    server_app.world_mut().syscall(token2, send_server_reject);
 
     std::thread::sleep(std::time::Duration::from_millis(50));
 
@@ -776,13 +1068,10 @@ fn client_loses_old_server_response()
     let request_id1 = token1.request_id();
     let request_id2 = token2.request_id();
 
-    server_app.world_mut().syscall(
-        (token1, DemoResponse1(1)),
-        send_server_response::<DemoRequest1, DemoResponse1>,
-    );
     server_app
         .world_mut()
-        .syscall(token2, send_server_ack::<DemoRequest1, DemoResponse1>);
+        .syscall((token1, DemoResponse1(1)), send_server_response::<DemoResponse1>);
+    server_app.world_mut().syscall(token2, send_server_ack);
     std::thread::sleep(std::time::Duration::from_millis(50));
     server_app
         .world_mut()
@@ -828,12 +1117,21 @@ fn client_send_blocked_until_read_connect()
     server_app.update();
     client_app.update();
 
-    assert!(!client_app.world_mut().syscall(DemoMsg1(1), try_send_client_message::<DemoMsg1>));
+    assert!(matches!(
+        client_app
+            .world_mut()
+            .syscall(DemoMsg1(1), try_send_client_message::<DemoMsg1>),
+        Err(SendError::PendingConnect)
+    ));
 
     assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
     assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
 
-    assert!(client_app.world_mut().syscall(DemoMsg1(10), try_send_client_message::<DemoMsg1>));
+    let signal = client_app
+        .world_mut()
+        .syscall(DemoMsg1(10), try_send_client_message::<DemoMsg1>)
+        .unwrap();
+    assert_ne!(signal.status(), MessageStatus::Failed);
 
     std::thread::sleep(std::time::Duration::from_millis(50));
 
@@ -930,9 +1228,12 @@ fn server_send_blocked_until_read_connect()
     server_app.update();
     client_app.update();
 
-    server_app
-        .world_mut()
-        .syscall((client_id, DemoMsg1(1)), send_server_message::<DemoMsg1>);
+    assert!(matches!(
+        server_app
+            .world_mut()
+            .syscall((client_id, DemoMsg1(1)), try_send_server_message::<DemoMsg1>),
+        Err(SendError::PendingConnect)
+    ));
 
     assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
     assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
@@ -952,3 +1253,2352 @@ fn server_send_blocked_until_read_connect()
 }
 
 //-------------------------------------------------------------------------------------------------------------------
+
+// schema mismatch
+//client registers different events than the server
+//both sides report a mismatch
+#[test]
+fn schema_mismatch_reported()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+
+    setup_event_app(&mut server_app);
+    client_app
+        .register_simplenet_client_message::<DemoChannel, DemoMsg2>()
+        .register_simplenet_client_message::<DemoChannel, DemoMsg1>();
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    let server_mismatches = server_app
+        .world_mut()
+        .syscall((), get_schema_mismatches_server);
+    assert_eq!(server_mismatches.len(), 1);
+    assert_eq!(server_mismatches[0].0, client_id);
+    let difference = server_mismatches[0].1.first_difference.clone().unwrap();
+    assert_eq!(difference.local.unwrap().type_name, std::any::type_name::<DemoMsg1>());
+    assert_eq!(difference.remote.unwrap().type_name, std::any::type_name::<DemoMsg2>());
+
+    let client_mismatches = client_app
+        .world_mut()
+        .syscall((), get_schema_mismatches_client);
+    assert_eq!(client_mismatches.len(), 1);
+    assert_eq!(
+        client_mismatches[0].remote_fingerprint,
+        Some(server_mismatches[0].1.local_fingerprint)
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// schema match
+//client and server register the same events
+//neither side reports a mismatch
+#[test]
+fn schema_match_not_reported()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), get_schema_mismatches_server).len(), 0);
+    assert_eq!(client_app.world_mut().syscall((), get_schema_mismatches_client).len(), 0);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// schema mismatch with disconnect policy
+//client registers different events than the server
+//server disconnects the client
+#[test]
+fn schema_mismatch_disconnects()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+
+    setup_event_app(&mut server_app);
+    server_app.set_simplenet_schema_policy::<DemoChannel>(SchemaPolicy::Disconnect);
+    client_app.register_simplenet_client_message::<DemoChannel, DemoMsg2>();
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    // the connect report is held until the client's schema is verified
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 0);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+    assert!(matches!(
+        server_app
+            .world_mut()
+            .syscall((client_id, DemoMsg1(1)), try_send_server_message::<DemoMsg1>),
+        Err(SendError::PendingConnect)
+    ));
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), get_schema_mismatches_server).len(), 1);
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 0);
+
+    std::thread::sleep(std::time::Duration::from_millis(200));
+
+    server_app.update();
+    client_app.update();
+
+    assert!(client_app.world_mut().syscall((), check_client_closed_by_server));
+
+    // the mismatched client is never reported as connected or disconnected
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 0);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// schema match with disconnect policy
+//client registers the same events as the server
+//server reports the connection once the client's schema arrives
+#[test]
+fn schema_verified_before_connect()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+    server_app.set_simplenet_schema_policy::<DemoChannel>(SchemaPolicy::Disconnect);
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 0);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), get_schema_mismatches_server).len(), 0);
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+
+    server_app
+        .world_mut()
+        .syscall((client_id, DemoMsg1(1)), send_server_message::<DemoMsg1>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    client_app.update();
+
+    assert!(client_app.world_mut().syscall(DemoMsg1(1), check_client_received_message::<DemoMsg1>));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// custom codec
+//client and server use a JSON channel
+//messages, requests, and responses round-trip
+#[test]
+fn custom_codec_round_trip()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let websocket_server = bevy_simplenet::ServerFactory::<EventWrapper<DemoJsonChannel>>::new("test").new_server(
+        enfync::builtin::native::TokioHandle::default(),
+        "127.0.0.1:0",
+        bevy_simplenet::AcceptorConfig::Default,
+        bevy_simplenet::Authenticator::None,
+        bevy_simplenet::ServerConfig::default(),
+    );
+    let client_id = 0u128;
+    let websocket_client = bevy_simplenet::ClientFactory::<EventWrapper<DemoJsonChannel>>::new("test").new_client(
+        enfync::builtin::Handle::adopt_or_default(),
+        websocket_server.url(),
+        bevy_simplenet::AuthRequest::None { client_id },
+        bevy_simplenet::ClientConfig::default(),
+        DemoConnectMsg(String::default()),
+    );
+    server_app.insert_simplenet_server(websocket_server);
+    client_app.insert_simplenet_client(websocket_client);
+
+    for app in [&mut server_app, &mut client_app] {
+        app.register_simplenet_server_message::<DemoJsonChannel, DemoMsg1>()
+            .register_simplenet_request_response::<DemoJsonChannel, DemoRequest1, DemoResponse1>();
+    }
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    server_app
+        .world_mut()
+        .syscall((), |reader: ServerConnectionReader<DemoJsonChannel>| {
+            reader.iter().count()
+        });
+    client_app
+        .world_mut()
+        .syscall((), |reader: ClientConnectionReader<DemoJsonChannel>| {
+            reader.iter().count()
+        });
+
+    client_app
+        .world_mut()
+        .syscall((), |client: EventClient<DemoJsonChannel>| {
+            client.send(DemoMsg1(1)).unwrap();
+            client.request(DemoRequest1(2)).unwrap();
+        });
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+
+    let messages = server_app
+        .world_mut()
+        .syscall((), |reader: ServerMessageReader<DemoJsonChannel, DemoMsg1>| {
+            reader
+                .iter()
+                .map(|(_, msg)| msg.clone())
+                .collect::<Vec<_>>()
+        });
+    assert_eq!(messages, vec![DemoMsg1(1)]);
+
+    let request_id = server_app.world_mut().syscall(
+        (),
+        |mut source: ServerRequestSource<DemoJsonChannel, DemoRequest1, DemoResponse1>,
+         server: EventServer<DemoJsonChannel>| {
+            let (token, request) = source.drain().next().unwrap();
+            assert_eq!(request, DemoRequest1(2));
+            let request_id = token.request_id();
+            server.respond(token, DemoResponse1(3)).unwrap();
+            request_id
+        },
+    );
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    client_app.update();
+
+    let responses = client_app.world_mut().syscall(
+        (),
+        |reader: ClientResponseReader<DemoJsonChannel, DemoRequest1, DemoResponse1>| {
+            reader
+                .iter()
+                .filter_map(|r| r.response().map(|resp| (resp.clone(), r.request_id())))
+                .collect::<Vec<_>>()
+        },
+    );
+    assert_eq!(responses, vec![(DemoResponse1(3), request_id)]);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// per-event codec
+//one message type uses a JSON codec in a bincode channel
+//both message types round-trip
+#[test]
+fn per_event_codec_round_trip()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+
+    for app in [&mut server_app, &mut client_app] {
+        setup_event_app(app);
+        app.register_simplenet_client_message_with_codec::<DemoChannel, DemoJsonMsg, DemoCountingJsonCodec>();
+    }
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    server_app.world_mut().syscall(
+        (client_id, DemoJsonMsg("hello".into())),
+        send_server_message::<DemoJsonMsg>,
+    );
+    server_app
+        .world_mut()
+        .syscall((client_id, DemoMsg1(1)), send_server_message::<DemoMsg1>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert!(client_app
+        .world_mut()
+        .syscall(DemoJsonMsg("hello".into()), check_client_received_message::<DemoJsonMsg>));
+    assert!(client_app.world_mut().syscall(DemoMsg1(1), check_client_received_message::<DemoMsg1>));
+    assert_eq!(JSON_DECODE_COUNT.load(std::sync::atomic::Ordering::Relaxed), 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// compressed event
+//a large message compressed with lz4 round-trips
+#[test]
+fn compressed_event_round_trip()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+
+    for app in [&mut server_app, &mut client_app] {
+        setup_event_app(app);
+        app.register_simplenet_client_message_with_codec::<DemoChannel, DemoSnapshot, Lz4Codec>();
+    }
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    let snapshot = DemoSnapshot(vec![42; 10_000]);
+    server_app
+        .world_mut()
+        .syscall((client_id, snapshot.clone()), send_server_message::<DemoSnapshot>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert!(client_app.world_mut().syscall(snapshot, check_client_received_message::<DemoSnapshot>));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// malformed server response
+//server responds to a request with the wrong response type
+//client resolves the request as malformed and reports a diagnostic instead of panicking
+#[test]
+fn client_malformed_response()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    client_app
+        .world_mut()
+        .syscall(DemoRequest2(1), send_client_request::<DemoRequest2>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    let mut reqs = server_app
+        .world_mut()
+        .syscall(client_id, get_server_requests::<DemoRequest2, ()>);
+    let (token, req) = reqs.pop().unwrap();
+    let request_id = token.request_id();
+    assert_eq!(req, DemoRequest2(1));
+
+    // DemoRequest2 expects a `()` response
+    server_app
+        .world_mut()
+        .syscall((token, DemoResponse1(2)), send_server_response::<DemoResponse1>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert!(client_app.world_mut().syscall(
+        ServerResponse::Malformed(request_id),
+        check_client_received_response::<DemoRequest2, ()>
+    ));
+    let diagnostics = client_app.world_mut().syscall((), get_client_diagnostics);
+    assert_eq!(diagnostics.len(), 1);
+    assert!(matches!(
+        diagnostics[0],
+        ClientDiagnostic::MalformedResponse { request_id: id, .. } if id == request_id
+    ));
+
+    // diagnostics are cleared every tick
+    client_app.update();
+    assert_eq!(client_app.world_mut().syscall((), get_client_diagnostics).len(), 0);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// unknown request id
+//a raw client sends a request that bypasses the event client
+//server responds to it
+//client reports the unknown request id and discards the response instead of panicking
+#[test]
+fn client_unknown_request_id()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    let mut websocket_client = demo_client_factory().new_client(
+        enfync::builtin::Handle::adopt_or_default(),
+        url,
+        bevy_simplenet::AuthRequest::None { client_id },
+        bevy_simplenet::ClientConfig::default(),
+        DemoConnectMsg(String::default()),
+    );
+
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+    server_app.register_simplenet_request_response::<DemoChannel, DemoPinnedRequest, DemoResponse1>();
+    client_app.register_simplenet_request_response::<DemoChannel, DemoPinnedRequest, DemoResponse1>();
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert!(matches!(
+        websocket_client.next(),
+        Some(bevy_simplenet::ClientEvent::Report(bevy_simplenet::ClientReport::Connected))
+    ));
+
+    // explicit ids are flagged with the high bit on the wire
+    let options = bincode::DefaultOptions::new();
+    let payload = options.serialize(&DemoPinnedRequest(1)).unwrap();
+    let request: InternalEvent = options
+        .deserialize(&options.serialize(&(0x8000u16 | 7, payload)).unwrap())
+        .unwrap();
+    let request_id = websocket_client.request(request).id();
+    client_app.insert_simplenet_client(websocket_client);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+
+    let mut reqs = server_app
+        .world_mut()
+        .syscall(client_id, get_server_requests::<DemoPinnedRequest, DemoResponse1>);
+    let (token, req) = reqs.pop().unwrap();
+    assert_eq!(token.request_id(), request_id);
+    assert_eq!(req, DemoPinnedRequest(1));
+    server_app
+        .world_mut()
+        .syscall((token, DemoResponse1(2)), send_server_response::<DemoResponse1>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    client_app.update();
+
+    let num_responses = client_app
+        .world_mut()
+        .syscall((), num_response_events_client::<DemoPinnedRequest, DemoResponse1>);
+    assert_eq!(num_responses, 0);
+    assert_eq!(
+        client_app.world_mut().syscall((), get_client_diagnostics),
+        vec![ClientDiagnostic::UnknownRequestId { request_id }]
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// decode errors
+//client and server send messages that fail to deserialize
+//server responds with a response that fails to deserialize
+//decode errors are reported on both sides, and the request resolves as malformed
+#[test]
+fn decode_errors_reported()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+
+    for app in [&mut server_app, &mut client_app] {
+        setup_event_app(app);
+        app.register_simplenet_client_message::<DemoChannel, DemoUndecodable>()
+            .register_simplenet_server_message::<DemoChannel, DemoUndecodable>()
+            .register_simplenet_request_response::<DemoChannel, DemoRequest3, DemoUndecodable>();
+    }
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    client_app
+        .world_mut()
+        .syscall(DemoUndecodable(1), send_client_message::<DemoUndecodable>);
+    client_app
+        .world_mut()
+        .syscall(DemoRequest3(2), send_client_request::<DemoRequest3>);
+    server_app
+        .world_mut()
+        .syscall((client_id, DemoUndecodable(3)), send_server_message::<DemoUndecodable>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    let server_errors = server_app.world_mut().syscall((), get_decode_errors_server);
+    assert_eq!(server_errors.len(), 1);
+    assert_eq!(server_errors[0].0, client_id);
+    assert_eq!(server_errors[0].2, std::any::type_name::<DemoUndecodable>());
+    let client_errors = client_app.world_mut().syscall((), get_decode_errors_client);
+    assert_eq!(client_errors.len(), 1);
+    assert_eq!(client_errors[0].1, std::any::type_name::<DemoUndecodable>());
+    assert_eq!(
+        client_app
+            .world_mut()
+            .syscall((), num_message_events_client::<DemoUndecodable>),
+        0
+    );
+
+    let mut reqs = server_app
+        .world_mut()
+        .syscall(client_id, get_server_requests::<DemoRequest3, DemoUndecodable>);
+    let (token, req) = reqs.pop().unwrap();
+    let request_id = token.request_id();
+    assert_eq!(req, DemoRequest3(2));
+
+    server_app
+        .world_mut()
+        .syscall((token, DemoUndecodable(4)), send_server_response::<DemoUndecodable>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert!(client_app.world_mut().syscall(
+        ServerResponse::Malformed(request_id),
+        check_client_received_response::<DemoRequest3, DemoUndecodable>
+    ));
+    let client_errors = client_app.world_mut().syscall((), get_decode_errors_client);
+    assert_eq!(client_errors.len(), 1);
+    assert_eq!(client_errors[0].1, std::any::type_name::<DemoUndecodable>());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// server multicast
+//server broadcasts, broadcasts except one client, and sends to a subset of clients
+//only the targeted clients receive each message
+//server sends to a connected client and an unknown client
+//the connected client receives the message and the unknown client is reported as a failure
+#[test]
+fn server_multicast()
+{
+    let mut server_app = App::new();
+    let mut client_app1 = App::new();
+    let mut client_app2 = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id1 = 0u128;
+    let client_id2 = 1u128;
+    setup_client(
+        &mut client_app1,
+        url.clone(),
+        client_id1,
+        DemoConnectMsg(String::default()),
+    );
+    setup_client(&mut client_app2, url, client_id2, DemoConnectMsg(String::default()));
+
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app1);
+    setup_event_app(&mut client_app2);
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app1.update();
+    client_app2.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 2);
+    assert_eq!(client_app1.world_mut().syscall((), num_connection_events_client), 1);
+    assert_eq!(client_app2.world_mut().syscall((), num_connection_events_client), 1);
+
+    server_app
+        .world_mut()
+        .syscall(DemoMsg1(1), broadcast_server_message::<DemoMsg1>);
+    server_app
+        .world_mut()
+        .syscall((client_id1, DemoMsg2(2)), broadcast_except_server_message::<DemoMsg2>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app1.update();
+    client_app2.update();
+
+    assert!(client_app1.world_mut().syscall(DemoMsg1(1), check_client_received_message::<DemoMsg1>));
+    assert!(client_app2.world_mut().syscall(DemoMsg1(1), check_client_received_message::<DemoMsg1>));
+    assert_eq!(client_app1.world_mut().syscall((), num_message_events_client::<DemoMsg2>), 0);
+    assert!(client_app2.world_mut().syscall(DemoMsg2(2), check_client_received_message::<DemoMsg2>));
+
+    server_app
+        .world_mut()
+        .syscall((vec![client_id1], DemoMsg1(3)), send_many_server_message::<DemoMsg1>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app1.update();
+    client_app2.update();
+
+    assert!(client_app1.world_mut().syscall(DemoMsg1(3), check_client_received_message::<DemoMsg1>));
+    assert_eq!(client_app2.world_mut().syscall((), num_message_events_client::<DemoMsg1>), 0);
+
+    let unknown_client_id = 99u128;
+    let result = server_app.world_mut().syscall(
+        (vec![client_id1, unknown_client_id], DemoMsg1(4)),
+        try_send_many_server_message::<DemoMsg1>,
+    );
+    let Err(SendManyError::Clients(failures)) = result else {
+        panic!("expected send_many to report the unknown client: {result:?}");
+    };
+    assert_eq!(failures.len(), 1);
+    assert!(matches!(failures[0], (id, SendError::UnknownClient) if id == unknown_client_id));
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app1.update();
+    client_app2.update();
+
+    assert!(client_app1.world_mut().syscall(DemoMsg1(4), check_client_received_message::<DemoMsg1>));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// server rooms
+//server sends to a room with one member
+//only the member receives the message
+//the member is removed from the room when it disconnects
+#[test]
+fn server_rooms()
+{
+    let mut server_app = App::new();
+    let mut client_app1 = App::new();
+    let mut client_app2 = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id1 = 0u128;
+    let client_id2 = 1u128;
+    setup_client(
+        &mut client_app1,
+        url.clone(),
+        client_id1,
+        DemoConnectMsg(String::default()),
+    );
+    setup_client(&mut client_app2, url, client_id2, DemoConnectMsg(String::default()));
+
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app1);
+    setup_event_app(&mut client_app2);
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app1.update();
+    client_app2.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 2);
+    assert_eq!(client_app1.world_mut().syscall((), num_connection_events_client), 1);
+    assert_eq!(client_app2.world_mut().syscall((), num_connection_events_client), 1);
+
+    let room_id = {
+        let mut rooms = server_app
+            .world_mut()
+            .resource_mut::<ServerRooms<DemoChannel>>();
+        let room_id = rooms.create_room();
+        assert!(rooms.join(room_id, client_id1));
+        assert!(!rooms.join(room_id, client_id1));
+        assert_eq!(rooms.members(room_id).collect::<Vec<_>>(), vec![client_id1]);
+        room_id
+    };
+
+    server_app
+        .world_mut()
+        .syscall((room_id, DemoMsg1(1)), send_room_server_message::<DemoMsg1>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app1.update();
+    client_app2.update();
+
+    assert!(client_app1.world_mut().syscall(DemoMsg1(1), check_client_received_message::<DemoMsg1>));
+    assert_eq!(client_app2.world_mut().syscall((), num_message_events_client::<DemoMsg1>), 0);
+
+    server_app
+        .world_mut()
+        .syscall(client_id1, disconnect_client_on_server);
+
+    std::thread::sleep(std::time::Duration::from_millis(200));
+
+    server_app.update();
+
+    let rooms = server_app.world().resource::<ServerRooms<DemoChannel>>();
+    assert!(rooms.has_room(room_id));
+    assert!(!rooms.is_member(room_id, client_id1));
+    assert_eq!(rooms.rooms_of(client_id1).count(), 0);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// server send errors
+//server sends to an unknown client and sends an unregistered message type
+//both sends return errors
+#[test]
+fn server_send_errors()
+{
+    let mut server_app = App::new();
+    setup_server(&mut server_app);
+    setup_event_app(&mut server_app);
+
+    server_app.update();
+
+    assert!(matches!(
+        server_app
+            .world_mut()
+            .syscall((42u128, DemoMsg1(1)), try_send_server_message::<DemoMsg1>),
+        Err(SendError::UnknownClient)
+    ));
+    assert!(matches!(
+        server_app
+            .world_mut()
+            .syscall((42u128, DemoRequest1(1)), try_send_server_message::<DemoRequest1>),
+        Err(SendError::UnregisteredType)
+    ));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// client: send errors
+#[test]
+fn client_send_errors()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    setup_client(&mut client_app, url, 0u128, DemoConnectMsg(String::default()));
+    setup_event_app(&mut client_app);
+
+    assert!(matches!(
+        client_app
+            .world_mut()
+            .syscall(DemoRequest1(1), try_send_client_message::<DemoRequest1>),
+        Err(SendError::UnregisteredType)
+    ));
+    assert!(matches!(
+        client_app
+            .world_mut()
+            .syscall(DemoMsg1(1), try_send_client_request::<DemoMsg1>),
+        Err(SendError::UnregisteredType)
+    ));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// server: message cursors
+//client message
+//server reads with cursors across ticks
+#[test]
+fn server_message_cursor()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    client_app
+        .world_mut()
+        .syscall(DemoMsg1(1), send_client_message::<DemoMsg1>);
+    client_app
+        .world_mut()
+        .syscall(DemoMsg1(2), send_client_message::<DemoMsg1>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+
+    // each message is read once per system
+    assert_eq!(server_app.world_mut().syscall((), read_message_cursor_server::<DemoMsg1>), 2);
+    assert_eq!(server_app.world_mut().syscall((), read_message_cursor_server::<DemoMsg1>), 0);
+
+    // a system that skips a tick still sees the messages
+    server_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_message_events_server::<DemoMsg1>), 0);
+    assert_eq!(server_app.world_mut().syscall((), read_message_cursor_server::<DemoMsg1>), 0);
+    assert_eq!(server_app.world_mut().syscall((), read_message_cursor_server_late::<DemoMsg1>), 2);
+
+    // messages are dropped after two ticks
+    client_app
+        .world_mut()
+        .syscall(DemoMsg1(3), send_client_message::<DemoMsg1>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    server_app.update();
+    server_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), read_message_cursor_server::<DemoMsg1>), 0);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// client: message cursors
+//server message
+//client reads with cursors across ticks
+#[test]
+fn client_message_cursor()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    server_app
+        .world_mut()
+        .syscall((client_id, DemoMsg1(1)), send_server_message::<DemoMsg1>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    client_app.update();
+    client_app.update();
+
+    assert_eq!(client_app.world_mut().syscall((), num_message_events_client::<DemoMsg1>), 0);
+    assert_eq!(client_app.world_mut().syscall((), read_message_cursor_client::<DemoMsg1>), 1);
+    assert_eq!(client_app.world_mut().syscall((), read_message_cursor_client::<DemoMsg1>), 0);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// server: refresh in a custom schedule
+//client message
+//server only sees the message when its schedule runs
+#[test]
+fn server_refresh_in_custom_schedule()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server_in(&mut server_app, DemoTick);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+
+    server_app.world_mut().run_schedule(DemoTick);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.world_mut().run_schedule(DemoTick);
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    client_app
+        .world_mut()
+        .syscall(DemoMsg1(1), send_client_message::<DemoMsg1>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    // the main schedule doesn't refresh the server
+    server_app.update();
+    assert_eq!(server_app.world_mut().syscall((), num_message_events_server::<DemoMsg1>), 0);
+
+    server_app.world_mut().run_schedule(DemoTick);
+    assert_eq!(server_app.world_mut().syscall((), num_message_events_server::<DemoMsg1>), 1);
+    assert_eq!(server_app.world_mut().syscall((), read_message_cursor_server::<DemoMsg1>), 1);
+
+    server_app.world_mut().run_schedule(DemoTick);
+    assert_eq!(server_app.world_mut().syscall((), num_message_events_server::<DemoMsg1>), 0);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// message triggers
+//client message triggers an observer on the server
+//server message triggers an observer on the client
+#[test]
+fn message_triggers()
+{
+    #[derive(Resource, Default)]
+    struct Received(Vec<(Option<ClientId>, usize)>);
+
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+
+    server_app
+        .init_resource::<Received>()
+        .add_simplenet_server_trigger::<DemoChannel, DemoMsg1>()
+        .add_observer(|trigger: Trigger<NetMsg<DemoMsg1>>, mut received: ResMut<Received>| {
+            received
+                .0
+                .push((Some(trigger.client_id), trigger.message.0));
+        });
+    client_app
+        .init_resource::<Received>()
+        .add_simplenet_client_trigger::<DemoChannel, DemoMsg2>()
+        .add_observer(
+            |trigger: Trigger<NetServerMsg<DemoMsg2>>, mut received: ResMut<Received>| {
+                received.0.push((None, trigger.message.0));
+            },
+        );
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    client_app
+        .world_mut()
+        .syscall(DemoMsg1(1), send_client_message::<DemoMsg1>);
+    client_app
+        .world_mut()
+        .syscall(DemoMsg1(2), send_client_message::<DemoMsg1>);
+    server_app
+        .world_mut()
+        .syscall((client_id, DemoMsg2(3)), send_server_message::<DemoMsg2>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world().resource::<Received>().0, vec![(Some(client_id), 1), (Some(client_id), 2)]);
+    assert_eq!(client_app.world().resource::<Received>().0, vec![(None, 3)]);
+
+    // observers only see new messages
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world().resource::<Received>().0.len(), 2);
+    assert_eq!(client_app.world().resource::<Received>().0.len(), 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// bevy event bridge
+//client sends with ToServer, server reads FromClient
+//server sends with ToClient, client reads FromServer
+#[test]
+fn bevy_event_bridge()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+
+    server_app
+        .add_simplenet_server_events::<DemoChannel, DemoMsg1>()
+        .add_simplenet_server_send_events::<DemoChannel, DemoMsg2>();
+    client_app
+        .add_simplenet_client_events::<DemoChannel, DemoMsg2>()
+        .add_simplenet_client_send_events::<DemoChannel, DemoMsg1>();
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    client_app
+        .world_mut()
+        .send_event(ToServer { message: DemoMsg1(1) });
+    server_app
+        .world_mut()
+        .send_event(ToClient { client_id, message: DemoMsg2(2) });
+    client_app.update();
+    server_app.update();
+
+    assert!(client_app.world().resource::<Events<ToServer<DemoMsg1>>>().is_empty());
+    assert!(server_app.world().resource::<Events<ToClient<DemoMsg2>>>().is_empty());
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    let received = server_app
+        .world_mut()
+        .syscall((), |mut reader: EventReader<FromClient<DemoMsg1>>| {
+            reader
+                .read()
+                .map(|e| (e.client_id, e.message.clone()))
+                .collect::<Vec<_>>()
+        });
+    assert_eq!(received, vec![(client_id, DemoMsg1(1))]);
+
+    let received = client_app
+        .world_mut()
+        .syscall((), |mut reader: EventReader<FromServer<DemoMsg2>>| {
+            reader.read().map(|e| e.message.clone()).collect::<Vec<_>>()
+        });
+    assert_eq!(received, vec![DemoMsg2(2)]);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// ToClient events can't be sent through two channels
+#[test]
+#[should_panic(expected = "was already added for channel")]
+fn bevy_event_bridge_server_second_channel_panics()
+{
+    let mut app = App::new();
+    app.register_simplenet_server_message::<DemoChannel, DemoMsg2>()
+        .register_simplenet_server_message::<DemoJsonChannel, DemoMsg2>()
+        .add_simplenet_server_send_events::<DemoChannel, DemoMsg2>()
+        .add_simplenet_server_send_events::<DemoJsonChannel, DemoMsg2>();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// ToServer events can't be sent through two channels
+#[test]
+#[should_panic(expected = "was already added for channel")]
+fn bevy_event_bridge_client_second_channel_panics()
+{
+    let mut app = App::new();
+    app.register_simplenet_client_message::<DemoChannel, DemoMsg1>()
+        .register_simplenet_client_message::<DemoJsonChannel, DemoMsg1>()
+        .add_simplenet_client_send_events::<DemoChannel, DemoMsg1>()
+        .add_simplenet_client_send_events::<DemoJsonChannel, DemoMsg1>();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// server: client entities
+//server spawns an entity for each client
+//entities are despawned or marked on disconnect
+#[test]
+fn server_client_entities()
+{
+    for policy in [ClientEntityPolicy::Despawn, ClientEntityPolicy::MarkDisconnected] {
+        let mut server_app = App::new();
+        let mut client_app = App::new();
+
+        let url = setup_server(&mut server_app);
+        let client_id = 0u128;
+        setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::from("hello")));
+        setup_event_app(&mut server_app);
+        setup_event_app(&mut client_app);
+
+        server_app.enable_simplenet_client_entities::<DemoChannel>(policy);
+
+        server_app.update();
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+
+        server_app.update();
+        client_app.update();
+
+        assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+        assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+        let entity = server_app
+            .world()
+            .resource::<ClientEntities<DemoChannel>>()
+            .get(client_id)
+            .unwrap();
+        let entity_ref = server_app.world().entity(entity);
+        assert_eq!(entity_ref.get::<NetClient>(), Some(&NetClient { id: client_id }));
+        assert_eq!(
+            entity_ref.get::<NetConnectMsg<DemoChannel>>().unwrap().0,
+            DemoConnectMsg(String::from("hello"))
+        );
+
+        server_app
+            .world_mut()
+            .syscall(client_id, disconnect_client_on_server);
+
+        std::thread::sleep(std::time::Duration::from_millis(200));
+
+        server_app.update();
+
+        let entities = server_app.world().resource::<ClientEntities<DemoChannel>>();
+        match policy {
+            ClientEntityPolicy::Despawn => {
+                assert_eq!(entities.get(client_id), None);
+                assert!(server_app.world().get_entity(entity).is_err());
+            }
+            ClientEntityPolicy::MarkDisconnected => {
+                assert_eq!(entities.get(client_id), Some(entity));
+                assert!(server_app.world().entity(entity).contains::<NetDisconnected>());
+            }
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// client: connection state
+//client connection state tracks connects and disconnects
+//run conditions follow the connection state
+#[test]
+fn client_connection_state()
+{
+    #[derive(Resource, Default)]
+    struct Runs
+    {
+        connected: usize,
+        just_connected: usize,
+        just_disconnected: usize,
+    }
+
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+
+    client_app.init_resource::<Runs>().add_systems(
+        Update,
+        (
+            (|mut runs: ResMut<Runs>| runs.connected += 1).run_if(client_connected::<DemoChannel>()),
+            (|mut runs: ResMut<Runs>| runs.just_connected += 1).run_if(client_just_connected::<DemoChannel>()),
+            (|mut runs: ResMut<Runs>| runs.just_disconnected += 1)
+                .run_if(client_just_disconnected::<DemoChannel>()),
+        ),
+    );
+
+    let state = client_app
+        .world()
+        .resource::<ClientConnectionState<DemoChannel>>();
+    assert_eq!(state.status(), ClientStatus::Connecting);
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    let state = client_app
+        .world()
+        .resource::<ClientConnectionState<DemoChannel>>();
+    assert_eq!(state.status(), ClientStatus::Connected);
+    assert!(state.just_connected());
+    assert!(!state.just_disconnected());
+
+    client_app.update();
+
+    let state = client_app
+        .world()
+        .resource::<ClientConnectionState<DemoChannel>>();
+    assert!(state.is_connected());
+    assert!(!state.just_connected());
+
+    server_app
+        .world_mut()
+        .syscall(client_id, disconnect_client_on_server);
+
+    std::thread::sleep(std::time::Duration::from_millis(200));
+
+    client_app.update();
+
+    let state = client_app
+        .world()
+        .resource::<ClientConnectionState<DemoChannel>>();
+    // clients closed by the server don't reconnect
+    assert_eq!(state.status(), ClientStatus::Dead);
+    assert!(state.just_disconnected());
+
+    let runs = client_app.world().resource::<Runs>();
+    assert_eq!(runs.connected, 2);
+    assert_eq!(runs.just_connected, 1);
+    assert_eq!(runs.just_disconnected, 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// client: connection run conditions in a schedule that doesn't run once per refresh
+//client connects, and the schedule runs twice
+//run conditions are true once
+//server disconnects the client, and the schedule runs after the next refresh
+//run conditions see the disconnect
+#[test]
+fn client_connection_run_conditions_uneven_schedule()
+{
+    #[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+    struct Uneven;
+
+    #[derive(Resource, Default)]
+    struct Runs
+    {
+        just_connected: usize,
+        just_disconnected: usize,
+    }
+
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+
+    client_app.init_resource::<Runs>().add_systems(
+        Uneven,
+        (
+            (|mut runs: ResMut<Runs>| runs.just_connected += 1).run_if(client_just_connected::<DemoChannel>()),
+            (|mut runs: ResMut<Runs>| runs.just_disconnected += 1)
+                .run_if(client_just_disconnected::<DemoChannel>()),
+        ),
+    );
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    // the schedule runs twice after one refresh
+    client_app.world_mut().run_schedule(Uneven);
+    client_app.world_mut().run_schedule(Uneven);
+
+    let runs = client_app.world().resource::<Runs>();
+    assert_eq!(runs.just_connected, 1);
+    assert_eq!(runs.just_disconnected, 0);
+
+    server_app
+        .world_mut()
+        .syscall(client_id, disconnect_client_on_server);
+
+    std::thread::sleep(std::time::Duration::from_millis(200));
+
+    // the schedule skips the refresh with the disconnect
+    client_app.update();
+    client_app.update();
+
+    let state = client_app
+        .world()
+        .resource::<ClientConnectionState<DemoChannel>>();
+    assert!(!state.just_disconnected());
+
+    client_app.world_mut().run_schedule(Uneven);
+
+    let runs = client_app.world().resource::<Runs>();
+    assert_eq!(runs.just_connected, 1);
+    assert_eq!(runs.just_disconnected, 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// client states plugin
+//client connects
+//state enters the connected state before Update, and OnEnter systems must read the connection report to send
+//server disconnects the client
+//state enters the disconnected state
+#[cfg(feature = "states")]
+#[test]
+fn client_states_plugin()
+{
+    use bevy_state::app::StatesPlugin;
+    use bevy_state::prelude::*;
+
+    #[derive(States, Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+    enum MenuState
+    {
+        #[default]
+        MainMenu,
+        InGame,
+    }
+
+    #[derive(Resource, Default)]
+    struct Log(Vec<&'static str>);
+
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    client_app.add_plugins(StatesPlugin);
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+
+    client_app
+        .add_plugins(SimplenetClientStatesPlugin::<DemoChannel, MenuState>::new(
+            MenuState::InGame,
+            MenuState::MainMenu,
+        ))
+        .add_plugins(SimplenetClientStatesPlugin::<DemoChannel, NetworkState<DemoChannel>>::default())
+        .init_resource::<Log>()
+        .add_systems(
+            OnEnter(MenuState::InGame),
+            |mut log: ResMut<Log>,
+             reader: ClientConnectionReader<DemoChannel>,
+             client: EventClient<DemoChannel>| {
+                // the plugin doesn't read the connection report
+                assert!(matches!(client.send(DemoMsg1(0)), Err(SendError::PendingConnect)));
+                reader.iter().for_each(|_| ());
+                assert!(client.send(DemoMsg1(42)).is_ok());
+                log.0.push("enter_game");
+            },
+        )
+        .add_systems(OnExit(MenuState::InGame), |mut log: ResMut<Log>| {
+            log.0.push("exit_game")
+        })
+        .add_systems(OnEnter(MenuState::MainMenu), |mut log: ResMut<Log>| {
+            log.0.push("enter_menu")
+        })
+        .add_systems(
+            Update,
+            (|mut log: ResMut<Log>| log.0.push("connected")).run_if(client_just_connected::<DemoChannel>()),
+        );
+
+    assert_eq!(*client_app.world().resource::<State<MenuState>>().get(), MenuState::MainMenu);
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(*client_app.world().resource::<State<MenuState>>().get(), MenuState::InGame);
+    assert_eq!(
+        *client_app
+            .world()
+            .resource::<State<NetworkState<DemoChannel>>>()
+            .get(),
+        NetworkState::Connected
+    );
+    assert_eq!(client_app.world().resource::<Log>().0, vec!["enter_menu", "enter_game", "connected"]);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    server_app.update();
+    assert_eq!(
+        server_app
+            .world_mut()
+            .syscall((), num_message_events_server::<DemoMsg1>),
+        1
+    );
+
+    server_app
+        .world_mut()
+        .syscall(client_id, disconnect_client_on_server);
+
+    std::thread::sleep(std::time::Duration::from_millis(200));
+
+    client_app.update();
+
+    assert_eq!(*client_app.world().resource::<State<MenuState>>().get(), MenuState::MainMenu);
+    assert_eq!(
+        *client_app
+            .world()
+            .resource::<State<NetworkState<DemoChannel>>>()
+            .get(),
+        NetworkState::Disconnected
+    );
+    assert_eq!(
+        client_app.world().resource::<Log>().0,
+        vec!["enter_menu", "enter_game", "connected", "exit_game", "enter_menu"]
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// client states plugin w/ reconnect in one refresh
+//client connects
+//server disconnects the client, and the client reconnects before its next refresh
+//state stays connected and applies an identity transition
+//OnTransition systems can read the new connection report
+#[cfg(feature = "states")]
+#[test]
+fn client_states_plugin_reconnect()
+{
+    use bevy_state::app::StatesPlugin;
+    use bevy_state::prelude::*;
+
+    #[derive(States, Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+    enum MenuState
+    {
+        #[default]
+        MainMenu,
+        InGame,
+    }
+
+    #[derive(Resource, Default)]
+    struct Log(Vec<&'static str>);
+
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    let websocket_client = demo_client_factory().new_client(
+        enfync::builtin::Handle::adopt_or_default(),
+        url,
+        bevy_simplenet::AuthRequest::None { client_id },
+        bevy_simplenet::ClientConfig {
+            reconnect_on_server_close: true,
+            reconnect_interval: std::time::Duration::from_millis(10),
+            ..Default::default()
+        },
+        DemoConnectMsg(String::default()),
+    );
+    client_app.add_plugins(StatesPlugin);
+    client_app.insert_simplenet_client(websocket_client);
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+
+    client_app
+        .add_plugins(SimplenetClientStatesPlugin::<DemoChannel, MenuState>::new(
+            MenuState::InGame,
+            MenuState::MainMenu,
+        ))
+        .init_resource::<Log>()
+        .add_systems(
+            OnEnter(MenuState::InGame),
+            |mut log: ResMut<Log>,
+             reader: ClientConnectionReader<DemoChannel>,
+             client: EventClient<DemoChannel>| {
+                reader.iter().for_each(|_| ());
+                assert!(client.send(DemoMsg1(42)).is_ok());
+                log.0.push("enter_game");
+            },
+        )
+        .add_systems(OnExit(MenuState::InGame), |mut log: ResMut<Log>| {
+            log.0.push("exit_game")
+        })
+        .add_systems(OnEnter(MenuState::MainMenu), |mut log: ResMut<Log>| {
+            log.0.push("enter_menu")
+        })
+        .add_systems(OnExit(MenuState::MainMenu), |mut log: ResMut<Log>| {
+            log.0.push("exit_menu")
+        })
+        .add_systems(
+            OnTransition { exited: MenuState::InGame, entered: MenuState::InGame },
+            |mut log: ResMut<Log>,
+             reader: ClientConnectionReader<DemoChannel>,
+             client: EventClient<DemoChannel>| {
+                reader.iter().for_each(|_| ());
+                assert!(client.send(DemoMsg1(42)).is_ok());
+                log.0.push("reenter_game");
+            },
+        );
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(*client_app.world().resource::<State<MenuState>>().get(), MenuState::InGame);
+    assert_eq!(client_app.world().resource::<Log>().0, vec!["enter_menu", "exit_menu", "enter_game"]);
+
+    server_app
+        .world_mut()
+        .syscall(client_id, disconnect_client_on_server);
+
+    std::thread::sleep(std::time::Duration::from_millis(300));
+
+    client_app.update();
+
+    let state = client_app
+        .world()
+        .resource::<ClientConnectionState<DemoChannel>>();
+    assert!(state.is_connected());
+    assert!(state.just_disconnected());
+    assert_eq!(*client_app.world().resource::<State<MenuState>>().get(), MenuState::InGame);
+    assert_eq!(
+        client_app.world().resource::<Log>().0,
+        vec!["enter_menu", "exit_menu", "enter_game", "reenter_game"]
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// server request w/ response/ack/reject/lost
+//server requests
+//client responds, acks, and drops a token
+//server receives
+//server requests and disconnects the client
+#[test]
+fn server_request()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+    server_app.register_simplenet_server_request_response::<DemoChannel, DemoServerRequest, DemoClientResponse>();
+    client_app.register_simplenet_server_request_response::<DemoChannel, DemoServerRequest, DemoClientResponse>();
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    //note: must read connection events before sending is allowed
+    assert!(matches!(
+        server_app
+            .world_mut()
+            .syscall((client_id, DemoServerRequest(0)), send_server_request::<DemoServerRequest>),
+        Err(SendError::PendingConnect)
+    ));
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    let mut request_ids = Vec::default();
+    for i in 1..=3 {
+        request_ids.push(
+            server_app
+                .world_mut()
+                .syscall(
+                    (client_id, DemoServerRequest(i)),
+                    send_server_request::<DemoServerRequest>,
+                )
+                .unwrap(),
+        );
+    }
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    let mut requests = client_app
+        .world_mut()
+        .syscall((), get_client_requests::<DemoServerRequest, DemoClientResponse>);
+    assert_eq!(requests.len(), 3);
+    let (token3, req3) = requests.pop().unwrap();
+    let (token2, req2) = requests.pop().unwrap();
+    let (token1, req1) = requests.pop().unwrap();
+    assert_eq!((token1.request_id(), req1), (request_ids[0], DemoServerRequest(1)));
+    assert_eq!((token2.request_id(), req2), (request_ids[1], DemoServerRequest(2)));
+    assert_eq!((token3.request_id(), req3), (request_ids[2], DemoServerRequest(3)));
+
+    client_app.world_mut().syscall(
+        (token1, token2),
+        |In((token1, token2)), client: EventClient<DemoChannel>| {
+            client.respond(token1, DemoClientResponse(10)).unwrap();
+            client.ack(token2).unwrap();
+        },
+    );
+    // dropped tokens are rejected in the next refresh
+    drop(token3);
+
+    client_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+
+    let responses = server_app
+        .world_mut()
+        .syscall((), get_server_responses::<DemoServerRequest, DemoClientResponse>);
+    assert!(responses == vec![
+        (client_id, ClientResponse::Response(DemoClientResponse(10), request_ids[0])),
+        (client_id, ClientResponse::Ack(request_ids[1])),
+        (client_id, ClientResponse::Reject(request_ids[2])),
+    ]);
+
+    // outstanding requests are lost when the client disconnects
+    let request_id = server_app
+        .world_mut()
+        .syscall(
+            (client_id, DemoServerRequest(4)),
+            send_server_request::<DemoServerRequest>,
+        )
+        .unwrap();
+    server_app
+        .world_mut()
+        .syscall(client_id, disconnect_client_on_server);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+
+    let responses = server_app
+        .world_mut()
+        .syscall((), get_server_responses::<DemoServerRequest, DemoClientResponse>);
+    assert!(responses == vec![(client_id, ClientResponse::ResponseLost(request_id))]);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// server request timeouts
+//server requests with the default timeout, a short timeout, and a long timeout
+//client does not respond until the timeouts elapse
+//server receives timeouts, late responses are ignored
+#[test]
+fn server_request_timeout()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+    server_app.register_simplenet_server_request_response::<DemoChannel, DemoServerRequest, DemoClientResponse>();
+    client_app.register_simplenet_server_request_response::<DemoChannel, DemoServerRequest, DemoClientResponse>();
+    server_app.set_simplenet_server_request_timeout::<DemoChannel, DemoServerRequest>(
+        std::time::Duration::from_millis(20),
+    );
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    // default timeout, short per-call timeout, and long per-call timeout
+    let timed_out_default = server_app
+        .world_mut()
+        .syscall(
+            (client_id, DemoServerRequest(1)),
+            send_server_request::<DemoServerRequest>,
+        )
+        .unwrap();
+    let (timed_out_call, long_timeout) =
+        server_app
+            .world_mut()
+            .syscall(client_id, |In(client_id), server: EventServer<DemoChannel>| {
+                let timeout = std::time::Duration::from_millis(20);
+                let timed_out = server
+                    .request_with_timeout(client_id, DemoServerRequest(2), timeout)
+                    .unwrap();
+                let long_timeout = server
+                    .request_with_timeout(client_id, DemoServerRequest(3), std::time::Duration::from_secs(60))
+                    .unwrap();
+                (timed_out, long_timeout)
+            });
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    let responses = server_app
+        .world_mut()
+        .syscall((), get_server_responses::<DemoServerRequest, DemoClientResponse>);
+    assert!(responses == vec![
+        (client_id, ClientResponse::TimedOut(timed_out_default)),
+        (client_id, ClientResponse::TimedOut(timed_out_call)),
+    ]);
+
+    // late responses are ignored
+    let requests = client_app
+        .world_mut()
+        .syscall((), get_client_requests::<DemoServerRequest, DemoClientResponse>);
+    assert_eq!(requests.len(), 3);
+    client_app.world_mut().syscall(
+        requests,
+        |In(requests): In<Vec<(ServerRequestToken, DemoServerRequest)>>, client: EventClient<DemoChannel>| {
+            for (token, req) in requests {
+                client.respond(token, DemoClientResponse(req.0)).unwrap();
+            }
+        },
+    );
+
+    client_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+
+    let responses = server_app
+        .world_mut()
+        .syscall((), get_server_responses::<DemoServerRequest, DemoClientResponse>);
+    assert!(responses == vec![(client_id, ClientResponse::Response(DemoClientResponse(3), long_timeout))]);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// server stream w/ chunks/done/error/lost
+//client sends stream requests
+//server sends chunks, ends one stream, and fails the other
+//client receives chunks in order followed by the terminal events
+//server sends a chunk and disconnects the client
+//client reconnects, server can't send chunks to the dead session
+#[test]
+fn server_stream()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    let websocket_client = demo_client_factory().new_client(
+        enfync::builtin::Handle::adopt_or_default(),
+        url,
+        bevy_simplenet::AuthRequest::None { client_id },
+        bevy_simplenet::ClientConfig {
+            reconnect_on_server_close: true,
+            reconnect_interval: std::time::Duration::from_millis(10),
+            ..Default::default()
+        },
+        DemoConnectMsg(String::default()),
+    );
+    client_app.insert_simplenet_client(websocket_client);
+
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+    server_app.register_simplenet_request_stream::<DemoChannel, DemoStreamRequest, DemoStreamChunk>();
+    client_app.register_simplenet_request_stream::<DemoChannel, DemoStreamRequest, DemoStreamChunk>();
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    let signal1 = client_app
+        .world_mut()
+        .syscall(DemoStreamRequest(1), try_send_client_request::<DemoStreamRequest>)
+        .unwrap();
+    let signal2 = client_app
+        .world_mut()
+        .syscall(DemoStreamRequest(2), try_send_client_request::<DemoStreamRequest>)
+        .unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+
+    let mut requests = server_app
+        .world_mut()
+        .syscall(client_id, get_server_requests::<DemoStreamRequest, DemoStreamChunk>);
+    assert_eq!(requests.len(), 2);
+    let (token2, _) = requests.pop().unwrap();
+    let (token1, _) = requests.pop().unwrap();
+
+    server_app.world_mut().syscall(
+        (token1, token2),
+        |In((token1, token2)), server: EventServer<DemoChannel>| {
+            server.send_chunk(&token1, DemoStreamChunk(10)).unwrap();
+            server.send_chunk(&token2, DemoStreamChunk(20)).unwrap();
+            server.send_chunk(&token1, DemoStreamChunk(11)).unwrap();
+            server.end_stream(token1).unwrap();
+            server.fail_stream(token2).unwrap();
+        },
+    );
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    client_app.update();
+
+    let id1 = signal1.id();
+    let id2 = signal2.id();
+    let stream = client_app
+        .world_mut()
+        .syscall((), get_client_stream::<DemoStreamRequest, DemoStreamChunk>);
+    assert!(stream == vec![
+        StreamResponse::Chunk(DemoStreamChunk(10), id1),
+        StreamResponse::Chunk(DemoStreamChunk(20), id2),
+        StreamResponse::Chunk(DemoStreamChunk(11), id1),
+        StreamResponse::Done(id1),
+        StreamResponse::Error(id2),
+    ]);
+    let request1 = client_app.world_mut().syscall(
+        id1,
+        |In(id), reader: ClientStreamReader<DemoChannel, DemoStreamRequest, DemoStreamChunk>| {
+            reader
+                .iter_request(id)
+                .filter_map(|r| r.chunk().cloned())
+                .collect::<Vec<_>>()
+        },
+    );
+    assert_eq!(request1, vec![DemoStreamChunk(10), DemoStreamChunk(11)]);
+
+    // open streams are lost when the client disconnects
+    let signal3 = client_app
+        .world_mut()
+        .syscall(DemoStreamRequest(3), try_send_client_request::<DemoStreamRequest>)
+        .unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+
+    let mut requests = server_app
+        .world_mut()
+        .syscall(client_id, get_server_requests::<DemoStreamRequest, DemoStreamChunk>);
+    assert_eq!(requests.len(), 1);
+    let (token3, _) = requests.pop().unwrap();
+    let token3 = server_app
+        .world_mut()
+        .syscall(token3, |In(token3), server: EventServer<DemoChannel>| {
+            server.send_chunk(&token3, DemoStreamChunk(30)).unwrap();
+            // keep the token alive, dropping it would fail the stream
+            token3
+        });
+    server_app
+        .world_mut()
+        .syscall(client_id, disconnect_client_on_server);
+
+    std::thread::sleep(std::time::Duration::from_millis(300));
+
+    server_app.update();
+    client_app.update();
+
+    // chunks for the dead session are not sent to the reconnected client
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 2);
+    let result = server_app
+        .world_mut()
+        .syscall(token3, |In(token3), server: EventServer<DemoChannel>| {
+            server.send_chunk(&token3, DemoStreamChunk(31))
+        });
+    assert!(matches!(result, Err(SendError::StaleRequest)), "{result:?}");
+
+    let stream = client_app
+        .world_mut()
+        .syscall((), get_client_stream::<DemoStreamRequest, DemoStreamChunk>);
+    assert!(stream == vec![StreamResponse::ResponseLost(signal3.id())]);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// client request timeouts
+//client requests with the default timeout, a per-call timeout, and no timeout
+//server does not respond until the timeouts elapse
+//client receives timeouts, late responses are discarded
+#[test]
+fn client_request_timeout()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+    client_app.set_simplenet_request_timeout::<DemoChannel, DemoRequest2>(std::time::Duration::from_millis(20));
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    // default timeout, per-call timeout, and no timeout
+    let timed_out_default = client_app
+        .world_mut()
+        .syscall(DemoRequest2(1), try_send_client_request::<DemoRequest2>)
+        .unwrap()
+        .id();
+    let (timed_out_call, untimed) = client_app
+        .world_mut()
+        .syscall((), |client: EventClient<DemoChannel>| {
+            let timeout = std::time::Duration::from_millis(20);
+            let timed_out = client
+                .request_with_timeout(DemoRequest1(2), timeout)
+                .unwrap();
+            let untimed = client.request(DemoRequest1(3)).unwrap();
+            (timed_out.id(), untimed.id())
+        });
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert!(client_app.world_mut().syscall(
+        ServerResponse::TimedOut(timed_out_default),
+        check_client_received_response::<DemoRequest2, ()>
+    ));
+    assert!(client_app.world_mut().syscall(
+        ServerResponse::TimedOut(timed_out_call),
+        check_client_received_response::<DemoRequest1, DemoResponse1>
+    ));
+    assert_eq!(client_app.world_mut().syscall((), num_response_events_client::<DemoRequest1, DemoResponse1>), 1);
+
+    // late responses are discarded
+    let mut acks = server_app
+        .world_mut()
+        .syscall(client_id, get_server_requests::<DemoRequest2, ()>);
+    assert_eq!(acks.len(), 1);
+    server_app
+        .world_mut()
+        .syscall(acks.pop().unwrap().0, send_server_ack);
+    let reqs = server_app
+        .world_mut()
+        .syscall(client_id, get_server_requests::<DemoRequest1, DemoResponse1>);
+    assert_eq!(reqs.len(), 2);
+    for (token, req) in reqs {
+        server_app
+            .world_mut()
+            .syscall((token, DemoResponse1(req.0)), send_server_response::<DemoResponse1>);
+    }
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    client_app.update();
+
+    assert_eq!(client_app.world_mut().syscall((), num_response_events_client::<DemoRequest2, ()>), 0);
+    assert_eq!(client_app.world_mut().syscall((), num_response_events_client::<DemoRequest1, DemoResponse1>), 1);
+    assert!(client_app.world_mut().syscall(
+        ServerResponse::Response(DemoResponse1(3), untimed),
+        check_client_received_response::<DemoRequest1, DemoResponse1>
+    ));
+    assert!(client_app.world_mut().syscall((), get_client_diagnostics).is_empty());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// client request cancellation
+//client sends two requests and cancels one
+//client receives the cancellation
+//server sees the cancellation notice and responds to both
+//client discards the late response
+#[test]
+fn client_request_cancel()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    let (cancelled, kept) = client_app
+        .world_mut()
+        .syscall((), |client: EventClient<DemoChannel>| {
+            let cancelled = client.request(DemoRequest1(1)).unwrap().id();
+            let kept = client.request(DemoRequest1(2)).unwrap().id();
+            client.cancel(cancelled);
+            (cancelled, kept)
+        });
+
+    // cancelled requests resolve in the next refresh
+    client_app.update();
+
+    assert!(client_app.world_mut().syscall(
+        ServerResponse::Cancelled(cancelled),
+        check_client_received_response::<DemoRequest1, DemoResponse1>
+    ));
+    assert_eq!(client_app.world_mut().syscall((), num_response_events_client::<DemoRequest1, DemoResponse1>), 1);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+
+    // the server sees the cancellation notice
+    let reqs = server_app
+        .world_mut()
+        .syscall(client_id, get_server_requests::<DemoRequest1, DemoResponse1>);
+    assert_eq!(reqs.len(), 2);
+    for (token, req) in reqs {
+        let (token, is_cancelled) = server_app.world_mut().syscall(
+            token,
+            |In(token): In<RequestToken>, reader: ServerCancelReader<DemoChannel>| {
+                let is_cancelled = reader.is_cancelled(&token);
+                (token, is_cancelled)
+            },
+        );
+        assert_eq!(is_cancelled, token.request_id() == cancelled);
+        server_app
+            .world_mut()
+            .syscall((token, DemoResponse1(req.0)), send_server_response::<DemoResponse1>);
+    }
+    let cancels = server_app
+        .world_mut()
+        .syscall((), |reader: ServerCancelReader<DemoChannel>| {
+            reader.iter().collect::<Vec<_>>()
+        });
+    assert_eq!(cancels, vec![(client_id, cancelled)]);
+
+    // late responses to cancelled requests are discarded
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    client_app.update();
+
+    assert_eq!(client_app.world_mut().syscall((), num_response_events_client::<DemoRequest1, DemoResponse1>), 1);
+    assert!(client_app.world_mut().syscall(
+        ServerResponse::Response(DemoResponse1(2), kept),
+        check_client_received_response::<DemoRequest1, DemoResponse1>
+    ));
+    assert!(client_app.world_mut().syscall((), get_client_diagnostics).is_empty());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// client request cancellation after the request resolves
+//client sends a request and the server responds
+//client cancels the resolved request and an unknown request
+//server doesn't see a cancellation notice
+//client doesn't emit another response
+#[test]
+fn client_request_cancel_resolved()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    let signal = client_app
+        .world_mut()
+        .syscall(DemoRequest1(1), try_send_client_request::<DemoRequest1>)
+        .unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+
+    let mut reqs = server_app
+        .world_mut()
+        .syscall(client_id, get_server_requests::<DemoRequest1, DemoResponse1>);
+    assert_eq!(reqs.len(), 1);
+    let (token, req) = reqs.pop().unwrap();
+    server_app
+        .world_mut()
+        .syscall((token, DemoResponse1(req.0)), send_server_response::<DemoResponse1>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    client_app.update();
+
+    assert!(client_app.world_mut().syscall(
+        ServerResponse::Response(DemoResponse1(1), signal.id()),
+        check_client_received_response::<DemoRequest1, DemoResponse1>
+    ));
+
+    // cancel the resolved request and a request that was never sent
+    client_app.world_mut().syscall(
+        signal.id(),
+        |In(request_id): In<u64>, client: EventClient<DemoChannel>| {
+            client.cancel(request_id);
+            client.cancel(request_id + 100);
+        },
+    );
+
+    client_app.update();
+
+    assert_eq!(client_app.world_mut().syscall((), num_response_events_client::<DemoRequest1, DemoResponse1>), 0);
+    assert!(client_app.world_mut().syscall((), get_client_diagnostics).is_empty());
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+
+    // the server doesn't see a cancellation notice
+    let cancels = server_app
+        .world_mut()
+        .syscall((), |reader: ServerCancelReader<DemoChannel>| {
+            reader.iter().collect::<Vec<_>>()
+        });
+    assert!(cancels.is_empty());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// server pending requests
+//client sends requests, server parks them with deadlines
+//expired requests are rejected, a taken request is responded to
+//client receives the rejection and response
+//server discards a client's requests when it disconnects
+#[test]
+fn server_pending_requests()
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+
+    let url = setup_server(&mut server_app);
+    let client_id = 0u128;
+    setup_client(&mut client_app, url, client_id, DemoConnectMsg(String::default()));
+
+    setup_event_app(&mut server_app);
+    setup_event_app(&mut client_app);
+    server_app.enable_simplenet_pending_requests::<DemoChannel>();
+
+    server_app.update();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+    client_app.update();
+
+    assert_eq!(server_app.world_mut().syscall((), num_connection_events_server), 1);
+    assert_eq!(client_app.world_mut().syscall((), num_connection_events_client), 1);
+
+    for i in 1..=3 {
+        client_app
+            .world_mut()
+            .syscall(DemoRequest1(i), send_client_request::<DemoRequest1>);
+    }
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+
+    // park requests, one with a short deadline
+    let reqs = server_app
+        .world_mut()
+        .syscall(client_id, get_server_requests::<DemoRequest1, DemoResponse1>);
+    assert_eq!(reqs.len(), 3);
+    let request_ids: Vec<u64> = reqs.iter().map(|(token, _)| token.request_id()).collect();
+    server_app.world_mut().syscall(
+        reqs,
+        |In(reqs): In<Vec<(RequestToken, DemoRequest1)>>,
+         mut pending: ResMut<ServerPendingRequests<DemoChannel>>| {
+            for (token, req) in reqs {
+                let timeout = match req.0 {
+                    1 => std::time::Duration::from_millis(10),
+                    _ => std::time::Duration::from_secs(60),
+                };
+                pending.park(token, timeout);
+            }
+        },
+    );
+    let pending = server_app
+        .world()
+        .resource::<ServerPendingRequests<DemoChannel>>();
+    assert_eq!(pending.num_pending(client_id), 3);
+    assert_eq!(pending.iter_counts().collect::<Vec<_>>(), vec![(client_id, 3)]);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    // expired requests are rejected
+    server_app.update();
+
+    let mut pending = server_app
+        .world_mut()
+        .resource_mut::<ServerPendingRequests<DemoChannel>>();
+    assert_eq!(pending.num_pending(client_id), 2);
+    assert!(!pending.contains(client_id, request_ids[0]));
+    let token = pending.take(client_id, request_ids[1]).unwrap();
+    assert_eq!(pending.num_pending(client_id), 1);
+    server_app
+        .world_mut()
+        .syscall((token, DemoResponse1(2)), send_server_response::<DemoResponse1>);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    client_app.update();
+
+    assert!(client_app.world_mut().syscall(
+        ServerResponse::Reject(request_ids[0]),
+        check_client_received_response::<DemoRequest1, DemoResponse1>
+    ));
+    assert!(client_app.world_mut().syscall(
+        ServerResponse::Response(DemoResponse1(2), request_ids[1]),
+        check_client_received_response::<DemoRequest1, DemoResponse1>
+    ));
+
+    // a client's requests are discarded when it disconnects
+    server_app
+        .world_mut()
+        .syscall(client_id, disconnect_client_on_server);
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    server_app.update();
+
+    let pending = server_app
+        .world()
+        .resource::<ServerPendingRequests<DemoChannel>>();
+    assert_eq!(pending.num_pending(client_id), 0);
+    assert!(pending.is_empty());
+}
+
+//-------------------------------------------------------------------------------------------------------------------